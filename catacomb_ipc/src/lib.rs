@@ -5,31 +5,83 @@
 
 //! IPC socket communication.
 
+use std::env;
 use std::error::Error;
 use std::fmt::{self, Display, Formatter};
-use std::io::{BufRead, BufReader, Write};
-use std::net::Shutdown;
+use std::io::{self, BufRead, BufReader, Write};
 use std::os::unix::net::UnixStream;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 #[cfg(feature = "clap")]
 use std::str::FromStr;
-use std::{env, process};
+use std::time::Duration;
 
 #[cfg(feature = "clap")]
 use clap::error::{Error as ClapError, ErrorKind as ClapErrorKind};
 #[cfg(feature = "clap")]
-use clap::{Subcommand, ValueEnum};
+use clap::{Args, Subcommand, ValueEnum};
 use regex::{Error as RegexError, Regex};
 use serde::{Deserialize, Serialize};
 #[cfg(feature = "smithay")]
 use smithay::input::keyboard::ModifiersState;
 #[cfg(feature = "smithay")]
-use smithay::utils::{Logical, Point, Size, Transform};
+use smithay::utils::{Logical, Point, Rectangle, Size, Transform};
 #[cfg(feature = "clap")]
 use xkbcommon::xkb;
 #[cfg(feature = "clap")]
 use xkbcommon::xkb::keysyms;
 
+/// IPC protocol version.
+///
+/// This is incremented whenever existing messages change in an incompatible
+/// way. Newly added requests are reported through [`REQUESTS`] instead.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Names of all [`IpcMessage`] requests understood by this protocol version.
+pub const REQUESTS: &[&str] = &[
+    "Orientation",
+    "Scale",
+    "BindGesture",
+    "BindGestureKey",
+    "UnbindGesture",
+    "BindKey",
+    "UnbindKey",
+    "KeyboardConfig",
+    "Dpms",
+    "Cursor",
+    "Windows",
+    "Window",
+    "Layout",
+    "Subscribe",
+    "ListBindings",
+    "ListGestures",
+    "ListScales",
+    "Version",
+    "Reload",
+    "Outputs",
+    "SetMode",
+    "Rule",
+    "ListRules",
+    "Unscale",
+    "InputConfig",
+    "Timings",
+    "Theme",
+    "Screenshot",
+    "Record",
+    "StopRecording",
+    "IdleTimeout",
+    "ClearIdleTimeouts",
+    "ListIdleTimeouts",
+    "BindTouchGesture",
+    "UnbindTouchGesture",
+    "ListTouchGestures",
+    "BindEdgeSwipe",
+    "UnbindEdgeSwipe",
+    "ListEdgeSwipes",
+    "BindTapGesture",
+    "UnbindTapGesture",
+    "ListTapGestures",
+];
+
 /// IPC message format.
 #[cfg_attr(feature = "clap", derive(Subcommand))]
 #[derive(Deserialize, Serialize, Debug)]
@@ -58,6 +110,13 @@ pub enum IpcMessage {
         #[cfg_attr(feature = "clap", clap(long))]
         app_id: Option<String>,
     },
+    /// Remove per-window scale overrides.
+    Unscale {
+        /// App ID regex of the scale override; removes all overrides if
+        /// omitted.
+        #[cfg_attr(feature = "clap", clap(long))]
+        app_id: Option<String>,
+    },
     /// Add a gesture.
     BindGesture {
         /// App ID regex.
@@ -105,6 +164,100 @@ pub enum IpcMessage {
         /// Termination sector of the gesture.
         end: GestureSector,
     },
+    /// Add a multi-finger touch gesture.
+    BindTouchGesture {
+        /// App ID regex.
+        ///
+        /// The binding will be enabled when the focused window's App ID matches
+        /// the regex.
+        ///
+        /// Use `*` to bind the gesture globally.
+        app_id: String,
+        /// Number of fingers; must be at least two.
+        fingers: u8,
+        /// Gesture performed by the fingers.
+        gesture: TouchGesture,
+        /// Program this gesture should spawn.
+        program: String,
+        /// Arguments for this gesture's program.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+    /// Remove a multi-finger touch gesture.
+    UnbindTouchGesture {
+        /// App ID regex of the gesture.
+        app_id: String,
+        /// Number of fingers.
+        fingers: u8,
+        /// Gesture performed by the fingers.
+        gesture: TouchGesture,
+    },
+    /// Add a swipe gesture starting at a screen edge.
+    BindEdgeSwipe {
+        /// App ID regex.
+        ///
+        /// The binding will be enabled when the focused window's App ID matches
+        /// the regex.
+        ///
+        /// Use `*` to bind the gesture globally.
+        app_id: String,
+        /// Screen edge the swipe starts at.
+        ///
+        /// Swipes from the bottom edge start directly above the gesture handle.
+        edge: ScreenEdge,
+        /// Swipe direction; defaults to swiping away from the edge.
+        #[cfg_attr(feature = "clap", clap(long))]
+        direction: Option<SwipeDirection>,
+        /// Minimum swipe distance in logical pixels.
+        #[cfg_attr(feature = "clap", clap(long))]
+        min_distance: Option<u32>,
+        /// Minimum average swipe velocity in logical pixels per second.
+        #[cfg_attr(feature = "clap", clap(long))]
+        min_velocity: Option<u32>,
+        /// Program this gesture should spawn.
+        program: String,
+        /// Arguments for this gesture's program.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+    /// Remove swipe gestures starting at a screen edge.
+    UnbindEdgeSwipe {
+        /// App ID regex of the gesture.
+        app_id: String,
+        /// Screen edge the swipe starts at.
+        edge: ScreenEdge,
+        /// Swipe direction; removes all directions if omitted.
+        #[cfg_attr(feature = "clap", clap(long))]
+        direction: Option<SwipeDirection>,
+    },
+    /// Add a long-press or double-tap gesture.
+    BindTapGesture {
+        /// App ID regex.
+        ///
+        /// The binding will be enabled when the focused window's App ID matches
+        /// the regex.
+        ///
+        /// Use `*` to bind the gesture globally.
+        app_id: String,
+        /// Type of tap gesture.
+        gesture: TapGesture,
+        /// Sector of the gesture, or `handle` for the gesture handle.
+        location: TapLocation,
+        /// Program this gesture should spawn.
+        program: String,
+        /// Arguments for this gesture's program.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+    /// Remove a long-press or double-tap gesture.
+    UnbindTapGesture {
+        /// App ID regex of the gesture.
+        app_id: String,
+        /// Type of tap gesture.
+        gesture: TapGesture,
+        /// Sector of the gesture, or `handle` for the gesture handle.
+        location: TapLocation,
+    },
     /// Add a key.
     BindKey {
         /// App ID regex.
@@ -118,8 +271,25 @@ pub enum IpcMessage {
         #[cfg_attr(feature = "clap", clap(long, short))]
         mods: Option<Modifiers>,
         /// Point at which the key event's command gets executed.
+        ///
+        /// This is one of `press`, `repeat`, `release`, or `hold=<MS>` to
+        /// execute the command after holding the key for `MS` milliseconds.
+        ///
+        /// For key sequences, this applies to the last key.
         #[cfg_attr(feature = "clap", clap(long, default_value = "press"))]
         trigger: KeyTrigger,
+        /// Keys which must be pressed after the base key.
+        ///
+        /// Each key can be prefixed with `+`-separated modifiers (i.e.
+        /// `ctrl+q`). Since only the order of key presses matters, this can
+        /// also be used for key chords.
+        #[cfg_attr(feature = "clap", clap(long = "then", value_name = "KEY"))]
+        #[serde(default)]
+        sequence: Vec<KeyStep>,
+        /// Maximum time in milliseconds between key presses of a sequence.
+        #[cfg_attr(feature = "clap", clap(long, requires = "sequence"))]
+        #[serde(default)]
+        sequence_timeout: Option<u64>,
         /// Base key for this binding.
         key: Keysym,
         /// Program this gesture should spawn.
@@ -135,6 +305,10 @@ pub enum IpcMessage {
         /// Required modifiers.
         #[cfg_attr(feature = "clap", clap(long, short))]
         mods: Option<Modifiers>,
+        /// Keys pressed after the base key.
+        #[cfg_attr(feature = "clap", clap(long = "then", value_name = "KEY"))]
+        #[serde(default)]
+        sequence: Vec<KeyStep>,
         /// Base key for this binding.
         key: Keysym,
     },
@@ -166,6 +340,400 @@ pub enum IpcMessage {
         /// Desired touch cursor visibility.
         state: CliToggle,
     },
+    /// List all open windows.
+    Windows,
+    /// Reply for window list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    WindowsReply { windows: Vec<WindowInfo> },
+    /// Control application windows.
+    Window {
+        /// Action to perform on the window.
+        action: WindowAction,
+        /// App ID regex of the target window; only the first match is used.
+        #[cfg_attr(feature = "clap", clap(long, required_unless_present = "id"))]
+        app_id: Option<String>,
+        /// Unique ID of the target window.
+        #[cfg_attr(feature = "clap", clap(long, conflicts_with = "app_id"))]
+        id: Option<u64>,
+    },
+    /// Switch between window layouts.
+    Layout {
+        #[cfg_attr(feature = "clap", clap(subcommand))]
+        action: LayoutAction,
+    },
+    /// Stream compositor events.
+    Subscribe {
+        /// Event types of interest; leave empty to receive all events.
+        events: Vec<EventKind>,
+    },
+    /// List all key bindings.
+    ListBindings,
+    /// Reply for key binding list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    BindingsReply { bindings: Vec<KeyBindingInfo> },
+    /// List all gesture bindings.
+    ListGestures,
+    /// Reply for gesture binding list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    GesturesReply { gestures: Vec<GestureBindingInfo> },
+    /// List all multi-finger touch gesture bindings.
+    ListTouchGestures,
+    /// Reply for multi-finger touch gesture list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    TouchGesturesReply { gestures: Vec<TouchGestureBindingInfo> },
+    /// List all edge swipe gesture bindings.
+    ListEdgeSwipes,
+    /// Reply for edge swipe gesture list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    EdgeSwipesReply { swipes: Vec<EdgeSwipeBindingInfo> },
+    /// List all long-press and double-tap gesture bindings.
+    ListTapGestures,
+    /// Reply for tap gesture list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    TapGesturesReply { gestures: Vec<TapGestureBindingInfo> },
+    /// List all per-window scale overrides.
+    ListScales,
+    /// Reply for window scale list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    ScalesReply { scales: Vec<WindowScaleInfo> },
+    /// Query compositor and IPC protocol version.
+    Version,
+    /// Reply for version request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    VersionReply {
+        /// Compositor version.
+        version: String,
+        /// IPC protocol version.
+        protocol: u32,
+        /// Names of all supported requests.
+        requests: Vec<String>,
+    },
+    /// Reset bindings, rules, input settings and other runtime
+    /// configuration, then re-run the config files.
+    ///
+    /// The `CATACOMB_RELOAD` environment variable is set for the startup
+    /// script, which should be used to avoid spawning daemons multiple times.
+    Reload,
+    /// List all connected outputs.
+    Outputs,
+    /// Reply for output list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    OutputsReply { outputs: Vec<OutputInfo> },
+    /// Set per-app window rules.
+    ///
+    /// This replaces all previous rules for the same App ID regex, except
+    /// for window scales.
+    Rule {
+        /// App ID regex the rules apply to.
+        app_id: String,
+        /// Open the window in fullscreen.
+        #[cfg_attr(
+            feature = "clap",
+            clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")
+        )]
+        fullscreen: Option<bool>,
+        /// Exclude the window from keyboard focus.
+        #[cfg_attr(
+            feature = "clap",
+            clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")
+        )]
+        deny_focus: Option<bool>,
+        /// Output orientation while the window is focused.
+        #[cfg_attr(feature = "clap", clap(long))]
+        orientation: Option<Orientation>,
+        /// Hide window content in the overview.
+        #[cfg_attr(
+            feature = "clap",
+            clap(long, num_args = 0..=1, require_equals = true, default_missing_value = "true")
+        )]
+        no_thumbnail: Option<bool>,
+        /// Layout position for new windows.
+        #[cfg_attr(feature = "clap", clap(long))]
+        placement: Option<LayoutSlot>,
+    },
+    /// List all per-app window rules.
+    ListRules,
+    /// Reply for window rule list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    RulesReply { rules: Vec<WindowRuleInfo> },
+    /// Change the output's resolution or refresh rate.
+    ///
+    /// Omitted values are taken from the current mode.
+    SetMode {
+        /// Horizontal resolution in pixels.
+        #[cfg_attr(feature = "clap", clap(long))]
+        width: Option<i32>,
+        /// Vertical resolution in pixels.
+        #[cfg_attr(feature = "clap", clap(long))]
+        height: Option<i32>,
+        /// Refresh rate in Hz; defaults to the highest available rate.
+        #[cfg_attr(feature = "clap", clap(long))]
+        refresh: Option<i32>,
+    },
+    /// Configure libinput devices.
+    ///
+    /// Lists the input devices when no settings are changed.
+    InputConfig {
+        /// Name of the input device.
+        device: Option<String>,
+        #[cfg_attr(feature = "clap", clap(flatten))]
+        settings: InputSettings,
+    },
+    /// Reply for input device list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    InputConfigReply { devices: Vec<InputDeviceInfo> },
+    /// Change key repeat and touch gesture timings.
+    ///
+    /// Prints the current timings when no values are changed.
+    Timings {
+        #[cfg_attr(feature = "clap", clap(flatten))]
+        timings: InputTimings,
+    },
+    /// Reply for input timings request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    TimingsReply { timings: InputTimings },
+    /// Change compositor colors and the gesture handle size.
+    ///
+    /// Prints the current theme when no values are changed.
+    Theme {
+        #[cfg_attr(feature = "clap", clap(flatten))]
+        theme: ThemeSettings,
+    },
+    /// Reply for theme request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    ThemeReply { theme: ThemeSettings },
+    /// Save a screenshot as PNG.
+    Screenshot {
+        /// Output file path.
+        path: PathBuf,
+        /// Captured area in the format "X,Y WxH"; defaults to the whole output.
+        #[cfg_attr(feature = "clap", clap(long))]
+        region: Option<Geometry>,
+        /// Draw the touch cursor.
+        #[cfg_attr(feature = "clap", clap(long))]
+        include_cursor: bool,
+    },
+    /// Start recording the output as YUV4MPEG2 video.
+    Record {
+        /// Pipe the video to an encoder's STDIN instead of writing it to a
+        /// file.
+        #[cfg_attr(feature = "clap", clap(long))]
+        pipe: bool,
+        /// Output file path, or encoder program with `--pipe`.
+        target: PathBuf,
+        /// Arguments for the encoder program.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+    /// Stop the active screen recording.
+    StopRecording,
+    /// Add an action which is performed after a period without user input.
+    IdleTimeout {
+        /// Idle time in seconds.
+        timeout: u64,
+        #[cfg_attr(feature = "clap", clap(subcommand))]
+        action: IdleAction,
+    },
+    /// Remove all idle timeouts.
+    ClearIdleTimeouts,
+    /// List all idle timeouts.
+    ListIdleTimeouts,
+    /// Reply for idle timeout list request.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    IdleTimeoutsReply { timeouts: Vec<IdleTimeoutInfo> },
+    /// Generic reply for successful requests.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    Ok,
+    /// Generic reply for failed requests.
+    #[cfg_attr(feature = "clap", clap(skip))]
+    Error { kind: ErrorKind, message: String },
+}
+
+/// IPC request failure categories.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum ErrorKind {
+    /// Request could not be parsed.
+    InvalidMessage,
+    /// App ID is not a valid regex.
+    InvalidAppId,
+    /// Scale is not valid for this request.
+    InvalidScale,
+    /// Keysym is not valid for this request.
+    InvalidKeysym,
+    /// No window matched the request.
+    WindowNotFound,
+    /// No layout exists at the requested index.
+    LayoutNotFound,
+    /// No output is connected.
+    OutputNotFound,
+    /// No output mode matched the request.
+    ModeNotFound,
+    /// Output rejected the requested mode.
+    ModeChangeFailed,
+    /// Calibration matrix could not be parsed.
+    InvalidMatrix,
+    /// Screenshot could not be rendered or saved.
+    ScreenshotFailed,
+    /// Screen recording could not be started or stopped.
+    RecordingFailed,
+}
+
+/// IPC request failure.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct IpcError {
+    pub kind: ErrorKind,
+    pub message: String,
+}
+
+impl IpcError {
+    /// Create a new IPC request failure.
+    pub fn new(kind: ErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl Display for IpcError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for IpcError {}
+
+impl From<IpcError> for IpcMessage {
+    fn from(error: IpcError) -> Self {
+        Self::Error { kind: error.kind, message: error.message }
+    }
+}
+
+/// Window control actions.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowAction {
+    /// Switch to the window's layout and focus it.
+    Focus,
+    /// Ask the window to close.
+    Close,
+    /// Close the window and remove it immediately.
+    Kill,
+    /// Toggle fullscreen for the window.
+    Fullscreen,
+    /// Show the window as the active layout's primary window.
+    Primary,
+    /// Show the window as the active layout's secondary window.
+    Secondary,
+}
+
+/// Layout switching actions.
+#[cfg_attr(feature = "clap", derive(Subcommand))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutAction {
+    /// Switch to the layout at the specified index.
+    Activate {
+        /// Index of the layout.
+        index: usize,
+    },
+    /// Switch to the layout `n` layouts away from the active one.
+    Cycle {
+        /// Number of layouts to move; negative values cycle backwards.
+        #[cfg_attr(feature = "clap", clap(allow_negative_numbers = true))]
+        n: isize,
+    },
+    /// Swap the active layout's primary and secondary window.
+    Swap,
+}
+
+/// Actions performed after the session became idle.
+#[cfg_attr(feature = "clap", derive(Subcommand))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum IdleAction {
+    /// Turn off the display.
+    ///
+    /// The display is turned back on by the next key press, like after a
+    /// resume from suspend, or the usual wakeup inputs like a double-tap. The
+    /// key press which wakes up the display is not sent to any client.
+    DpmsOff,
+    /// Run a program.
+    Exec {
+        /// Program or script to execute.
+        program: String,
+        /// Arguments for the program.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+    /// Run a session locker, unless the session is already locked.
+    ///
+    /// The compositor does not lock the session by itself, it is only locked
+    /// once the program acquires a lock with the `ext-session-lock` protocol.
+    Lock {
+        /// Session locker to execute.
+        program: String,
+        /// Arguments for the session locker.
+        #[cfg_attr(feature = "clap", clap(allow_hyphen_values = true, trailing_var_arg = true))]
+        arguments: Vec<String>,
+    },
+}
+
+/// Compositor event sent to IPC subscribers.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub enum IpcEvent {
+    /// New window was mapped.
+    WindowOpen { window: WindowInfo },
+    /// Window was destroyed.
+    WindowClose { window: WindowInfo },
+    /// Keyboard focus changed.
+    Focus { window: Option<WindowInfo> },
+    /// Window arrangement or active layout changed.
+    Layout { active: Option<usize> },
+    /// Output orientation changed.
+    Orientation { orientation: Orientation },
+    /// Output power state changed.
+    Dpms { state: CliToggle },
+    /// Output scale changed.
+    Scale { scale: f64 },
+    /// Window user attention request changed.
+    Urgency { window: WindowInfo },
+}
+
+impl IpcEvent {
+    /// Get the subscription category of this event.
+    #[must_use]
+    pub fn kind(&self) -> EventKind {
+        match self {
+            Self::WindowOpen { .. } | Self::WindowClose { .. } => EventKind::Window,
+            Self::Focus { .. } => EventKind::Focus,
+            Self::Layout { .. } => EventKind::Layout,
+            Self::Orientation { .. } => EventKind::Orientation,
+            Self::Dpms { .. } => EventKind::Dpms,
+            Self::Scale { .. } => EventKind::Scale,
+            Self::Urgency { .. } => EventKind::Urgency,
+        }
+    }
+}
+
+/// IPC event subscription categories.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum EventKind {
+    /// Window creation and destruction.
+    Window,
+    /// Keyboard focus changes.
+    Focus,
+    /// Window arrangement changes.
+    Layout,
+    /// Output orientation changes.
+    Orientation,
+    /// Output power state changes.
+    Dpms,
+    /// Output scale changes.
+    Scale,
+    /// Window urgency changes.
+    Urgency,
 }
 
 /// Device orientation.
@@ -267,6 +835,87 @@ impl GestureSector {
     }
 }
 
+/// Multi-finger touch gestures.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TouchGesture {
+    SwipeUp,
+    SwipeDown,
+    SwipeLeft,
+    SwipeRight,
+    /// Move fingers closer together.
+    PinchIn,
+    /// Move fingers further apart.
+    PinchOut,
+    Tap,
+}
+
+/// Stationary touch gestures.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TapGesture {
+    LongPress,
+    DoubleTap,
+}
+
+/// Touch area of a tap gesture.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum TapLocation {
+    Sector(GestureSector),
+    Handle,
+}
+
+#[cfg(feature = "clap")]
+impl FromStr for TapLocation {
+    type Err = ClapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("handle") {
+            return Ok(Self::Handle);
+        }
+
+        match GestureSector::from_str(s, true) {
+            Ok(sector) => Ok(Self::Sector(sector)),
+            Err(_) => Err(ClapError::raw(
+                ClapErrorKind::InvalidValue,
+                format!("invalid location {s:?}, expected a gesture sector or \"handle\""),
+            )),
+        }
+    }
+}
+
+/// Output edges.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum ScreenEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl ScreenEdge {
+    /// Get the direction pointing away from this edge.
+    pub fn inward(&self) -> SwipeDirection {
+        match self {
+            Self::Top => SwipeDirection::Down,
+            Self::Bottom => SwipeDirection::Up,
+            Self::Left => SwipeDirection::Right,
+            Self::Right => SwipeDirection::Left,
+        }
+    }
+}
+
+/// Swipe gesture directions.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub enum SwipeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
 /// Window-specific scaling options.
 #[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Debug)]
 pub enum WindowScale {
@@ -455,8 +1104,29 @@ impl FromStr for Keysym {
     }
 }
 
+/// Single key press of a key sequence.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct KeyStep {
+    #[serde(default)]
+    pub mods: Modifiers,
+    pub key: Keysym,
+}
+
+#[cfg(feature = "clap")]
+impl FromStr for KeyStep {
+    type Err = ClapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.rsplit_once('+') {
+            Some((mods, key)) => {
+                Ok(Self { mods: mods.replace('+', ",").parse()?, key: key.parse()? })
+            },
+            None => Ok(Self { mods: Modifiers::default(), key: s.parse()? }),
+        }
+    }
+}
+
 /// Point at which a key event's command gets executed.
-#[cfg_attr(feature = "clap", derive(ValueEnum))]
 #[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
 pub enum KeyTrigger {
     /// Trigger on key down.
@@ -464,68 +1134,755 @@ pub enum KeyTrigger {
     /// Trigger on key down and key repeat.
     Repeat,
     /// Trigger on key up.
+    ///
+    /// Release bindings are skipped if a hold binding of the same key was
+    /// triggered.
     Release,
+    /// Trigger after the key was held for the specified number of
+    /// milliseconds.
+    Hold(u64),
+}
+
+#[cfg(feature = "clap")]
+impl FromStr for KeyTrigger {
+    type Err = ClapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "press" => Ok(Self::Press),
+            "repeat" => Ok(Self::Repeat),
+            "release" => Ok(Self::Release),
+            trigger => match trigger.strip_prefix("hold=").map(str::parse) {
+                Some(Ok(duration)) => Ok(Self::Hold(duration)),
+                _ => Err(ClapError::raw(
+                    ClapErrorKind::InvalidValue,
+                    format!(
+                        "invalid trigger {s:?}, expected one of \"press\", \"repeat\", \
+                         \"release\", or \"hold=<MILLISECONDS>\""
+                    ),
+                )),
+            },
+        }
+    }
+}
+
+/// Key binding reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct KeyBindingInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub mods: Modifiers,
+    pub key: Keysym,
+    pub trigger: KeyTrigger,
+    pub sequence: Vec<KeyStep>,
+    /// Maximum time between key presses of the sequence in milliseconds.
+    pub sequence_timeout: u64,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+/// Gesture binding reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct GestureBindingInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub start: GestureSector,
+    pub end: GestureSector,
+    pub action: GestureAction,
+}
+
+/// Multi-finger touch gesture binding reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct TouchGestureBindingInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub fingers: u8,
+    pub gesture: TouchGesture,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+/// Edge swipe gesture binding reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct EdgeSwipeBindingInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub edge: ScreenEdge,
+    pub direction: SwipeDirection,
+    /// Minimum swipe distance in logical pixels.
+    pub min_distance: u32,
+    /// Minimum average swipe velocity in logical pixels per second.
+    pub min_velocity: Option<u32>,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+/// Tap gesture binding reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct TapGestureBindingInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub gesture: TapGesture,
+    pub location: TapLocation,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+/// Idle timeout reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub struct IdleTimeoutInfo {
+    /// Idle time in seconds.
+    pub timeout: u64,
+    pub action: IdleAction,
+}
+
+/// Action executed by a gesture binding.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Clone, Debug)]
+pub enum GestureAction {
+    /// Run a program.
+    Cmd { program: String, arguments: Vec<String> },
+    /// Send a key press.
+    Key { mods: Modifiers, key: Keysym },
+}
+
+/// Window scale override reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct WindowScaleInfo {
+    /// App ID regex.
+    pub app_id: String,
+    pub scale: WindowScale,
+}
+
+/// Per-app window behavior overrides.
+#[derive(Deserialize, Serialize, Default, PartialEq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct WindowRule {
+    /// Window scale override.
+    pub scale: Option<WindowScale>,
+    /// Open the window in fullscreen.
+    pub fullscreen: Option<bool>,
+    /// Exclude the window from keyboard focus.
+    pub deny_focus: Option<bool>,
+    /// Output orientation while the window is focused.
+    pub orientation: Option<Orientation>,
+    /// Hide window content in the overview.
+    pub no_thumbnail: Option<bool>,
+    /// Layout position for new windows.
+    pub placement: Option<LayoutSlot>,
+}
+
+impl WindowRule {
+    /// Fill in options not set by this rule from a lower priority rule.
+    #[must_use]
+    pub fn merge(self, other: &Self) -> Self {
+        Self {
+            scale: self.scale.or(other.scale),
+            fullscreen: self.fullscreen.or(other.fullscreen),
+            deny_focus: self.deny_focus.or(other.deny_focus),
+            orientation: self.orientation.or(other.orientation),
+            no_thumbnail: self.no_thumbnail.or(other.no_thumbnail),
+            placement: self.placement.or(other.placement),
+        }
+    }
+}
+
+/// Window rule reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct WindowRuleInfo {
+    /// App ID regex.
+    pub app_id: String,
+    #[serde(flatten)]
+    pub rule: WindowRule,
+}
+
+/// Window state reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct WindowInfo {
+    /// Unique window identifier.
+    pub id: u64,
+    /// Application ID.
+    pub app_id: Option<String>,
+    /// Window title.
+    pub title: Option<String>,
+    /// Index of the window's layout.
+    pub layout: usize,
+    /// Position of the window inside its layout.
+    pub position: LayoutSlot,
+    /// Whether the window has keyboard focus.
+    pub focused: bool,
+    /// Whether the window requested user attention.
+    pub urgent: bool,
+    /// Whether the window is fullscreened.
+    pub fullscreen: bool,
+    /// Scale factor used by the window.
+    pub scale: f64,
+    /// Window bounds in the output's logical coordinates.
+    pub rectangle: Geometry,
+}
+
+/// Window position inside a layout.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum LayoutSlot {
+    /// Top or left window.
+    Primary,
+    /// Bottom or right window.
+    Secondary,
+}
+
+/// Rectangle in logical coordinates.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Default, Copy, Clone, Debug)]
+pub struct Geometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: i32,
+    pub height: i32,
+}
+
+#[cfg(feature = "smithay")]
+impl From<Rectangle<i32, Logical>> for Geometry {
+    fn from(rect: Rectangle<i32, Logical>) -> Self {
+        Self { x: rect.loc.x, y: rect.loc.y, width: rect.size.w, height: rect.size.h }
+    }
+}
+
+#[cfg(feature = "smithay")]
+impl From<Geometry> for Rectangle<i32, Logical> {
+    fn from(geometry: Geometry) -> Self {
+        Rectangle::new((geometry.x, geometry.y).into(), (geometry.width, geometry.height).into())
+    }
+}
+
+#[cfg(feature = "clap")]
+impl FromStr for Geometry {
+    type Err = ClapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_geometry = || {
+            let message = format!("invalid geometry {s:?}, expected \"X,Y WxH\"");
+            ClapError::raw(ClapErrorKind::InvalidValue, message)
+        };
+
+        let (position, size) = s.trim().split_once(' ').ok_or_else(invalid_geometry)?;
+        let (x, y) = position.split_once(',').ok_or_else(invalid_geometry)?;
+        let (width, height) = size.trim().split_once('x').ok_or_else(invalid_geometry)?;
+
+        let parse = |value: &str| i32::from_str(value.trim()).map_err(|_| invalid_geometry());
+        let geometry =
+            Self { x: parse(x)?, y: parse(y)?, width: parse(width)?, height: parse(height)? };
+
+        if geometry.width <= 0 || geometry.height <= 0 {
+            return Err(ClapError::raw(ClapErrorKind::InvalidValue, "geometry must not be empty"));
+        }
+
+        Ok(geometry)
+    }
+}
+
+/// Output state reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct OutputInfo {
+    /// DRM connector name.
+    pub name: String,
+    /// Physical width in millimeters.
+    pub physical_width: i32,
+    /// Physical height in millimeters.
+    pub physical_height: i32,
+    /// All modes supported by the output.
+    pub modes: Vec<OutputMode>,
+    /// Active output mode.
+    pub current_mode: OutputMode,
+    /// Output scale factor.
+    pub scale: f64,
+    /// Output orientation.
+    pub orientation: Orientation,
+}
+
+/// Output resolution and refresh rate.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct OutputMode {
+    /// Horizontal resolution in pixels.
+    pub width: i32,
+    /// Vertical resolution in pixels.
+    pub height: i32,
+    /// Refresh rate in Hz.
+    pub refresh: i32,
+}
+
+/// Libinput device settings.
+#[cfg_attr(feature = "clap", derive(Args))]
+#[derive(Deserialize, Serialize, Default, PartialEq, Clone, Debug)]
+#[serde(default)]
+pub struct InputSettings {
+    /// Tap-to-click.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tap: Option<CliToggle>,
+    /// Natural scrolling.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub natural_scroll: Option<CliToggle>,
+    /// Pointer acceleration profile.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accel_profile: Option<AccelProfile>,
+    /// Pointer acceleration speed, between -1 and 1.
+    #[cfg_attr(feature = "clap", clap(long, allow_hyphen_values = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accel_speed: Option<f64>,
+    /// Left-handed button mapping.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub left_handed: Option<CliToggle>,
+    /// Event processing; disabling this ignores all input from the device.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub send_events: Option<CliToggle>,
+    /// Touch calibration matrix, like "1, 0, 0; 0, 1, 0; 0, 0, 1".
+    ///
+    /// The last row must always be "0, 0, 1".
+    #[cfg_attr(feature = "clap", clap(long, allow_hyphen_values = true))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub calibration: Option<String>,
+    /// Touch processing; disabling this ignores touch input from the device.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub touch: Option<CliToggle>,
+}
+
+impl InputSettings {
+    /// Override settings with all options set in `other`.
+    pub fn update(&mut self, other: &Self) {
+        self.tap = other.tap.or(self.tap);
+        self.natural_scroll = other.natural_scroll.or(self.natural_scroll);
+        self.accel_profile = other.accel_profile.or(self.accel_profile);
+        self.accel_speed = other.accel_speed.or(self.accel_speed);
+        self.left_handed = other.left_handed.or(self.left_handed);
+        self.send_events = other.send_events.or(self.send_events);
+        self.calibration = other.calibration.clone().or(self.calibration.take());
+        self.touch = other.touch.or(self.touch);
+    }
+
+    /// Check if all settings are within their supported range.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.accel_speed.is_some_and(|speed| !(-1. ..=1.).contains(&speed)) {
+            return Err("acceleration speed must be between -1 and 1");
+        }
+
+        Ok(())
+    }
+}
+
+/// Pointer acceleration profile.
+#[cfg_attr(feature = "clap", derive(ValueEnum))]
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum AccelProfile {
+    /// Constant acceleration factor.
+    Flat,
+    /// Acceleration depending on pointer speed.
+    Adaptive,
+}
+
+/// Input device reported over IPC.
+#[derive(Deserialize, Serialize, PartialEq, Clone, Debug)]
+pub struct InputDeviceInfo {
+    /// Device name.
+    pub name: String,
+    /// Device capabilities.
+    pub capabilities: Vec<InputCapability>,
+    /// Current settings, omitting those unsupported by the device.
+    pub settings: InputSettings,
+}
+
+/// Input device capabilities.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(rename_all = "kebab-case")]
+pub enum InputCapability {
+    Keyboard,
+    Pointer,
+    Touch,
+    TabletTool,
+    TabletPad,
+    Gesture,
+    Switch,
+}
+
+/// Key repeat and touch gesture timings.
+#[cfg_attr(feature = "clap", derive(Args))]
+#[derive(Deserialize, Serialize, Default, PartialEq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct InputTimings {
+    /// Time in milliseconds before key repeat starts.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_delay: Option<u64>,
+    /// Key repeat interval in milliseconds.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repeat_interval: Option<u64>,
+    /// Time in milliseconds before a touch is considered a hold.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hold_duration: Option<u64>,
+    /// Maximum time in milliseconds between the taps of a double-tap.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub double_tap_duration: Option<u64>,
+    /// Maximum distance in pixels before a touch is considered a drag.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tap_distance: Option<f64>,
+    /// Touch velocity friction, between 0 and 1.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub friction: Option<f64>,
+}
+
+impl InputTimings {
+    /// Check if all timings are within their supported range.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.repeat_interval == Some(0) {
+            return Err("repeat interval must be greater than zero");
+        }
+
+        if self.tap_distance.is_some_and(|distance| !(0. ..).contains(&distance)) {
+            return Err("tap distance must not be negative");
+        }
+
+        if self.friction.is_some_and(|friction| !(0. ..=1.).contains(&friction)) {
+            return Err("friction must be between 0 and 1");
+        }
+
+        Ok(())
+    }
+}
+
+/// Compositor colors and gesture handle size.
+#[cfg_attr(feature = "clap", derive(Args))]
+#[derive(Deserialize, Serialize, Default, PartialEq, Eq, Copy, Clone, Debug)]
+#[serde(default)]
+pub struct ThemeSettings {
+    /// Gesture handle height at scale factor 1.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gesture_handle_height: Option<u32>,
+    /// Gesture handle notch color with automatic IME control.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gesture_handle_color: Option<Color>,
+    /// Gesture handle notch color with IME force-enabled.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gesture_handle_locked_color: Option<Color>,
+    /// Gesture handle notch color with IME force-disabled.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gesture_handle_blocked_color: Option<Color>,
+    /// Gesture handle background color.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gesture_handle_background_color: Option<Color>,
+    /// Touch cursor color.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cursor_color: Option<Color>,
+    /// Overview urgency indicator color.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub urgency_icon_color: Option<Color>,
+    /// Overview tiling location highlight color.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_target_color: Option<Color>,
+    /// Hovered overview tiling location highlight color.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub active_drop_target_color: Option<Color>,
+    /// Color of overview windows with hidden content.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thumbnail_placeholder_color: Option<Color>,
+    /// Color behind all windows.
+    #[cfg_attr(feature = "clap", clap(long))]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<Color>,
+}
+
+impl ThemeSettings {
+    /// Check if all theme settings are within their supported range.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.gesture_handle_height.is_some_and(|height| !(1..=100).contains(&height)) {
+            return Err("gesture handle height must be between 1 and 100");
+        }
+
+        Ok(())
+    }
+}
+
+/// RGBA color.
+#[derive(Deserialize, Serialize, PartialEq, Eq, Copy, Clone, Debug)]
+pub struct Color(pub [u8; 4]);
+
+#[cfg(feature = "clap")]
+impl FromStr for Color {
+    type Err = ClapError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid_color = || {
+            let message = format!("invalid color {s:?}, expected \"#RRGGBB\" or \"#RRGGBBAA\"");
+            ClapError::raw(ClapErrorKind::InvalidValue, message)
+        };
+
+        let hex = s.strip_prefix('#').unwrap_or(s);
+        if (hex.len() != 6 && hex.len() != 8) || !hex.is_ascii() {
+            return Err(invalid_color());
+        }
+
+        // Parse each channel, defaulting to full opacity.
+        let mut color = [255; 4];
+        for (i, channel) in color.iter_mut().take(hex.len() / 2).enumerate() {
+            *channel =
+                u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).map_err(|_| invalid_color())?;
+        }
+
+        Ok(Self(color))
+    }
 }
 
 /// Send a message to the Catacomb IPC socket.
-pub fn send_message(message: &IpcMessage) -> Result<Option<IpcMessage>, Box<dyn Error>> {
-    // Ensure IPC message is legal.
-    validate_message(message)?;
-
-    let socket_name = match env::var("WAYLAND_DISPLAY") {
-        Ok(socket_name) => socket_name,
-        Err(_) => {
-            eprintln!("Error: WAYLAND_DISPLAY is not set");
-            process::exit(101);
-        },
-    };
+///
+/// This connects to the socket of the compositor running on
+/// `WAYLAND_DISPLAY`, use [`Connection`] to send multiple messages.
+pub fn send_message(message: &IpcMessage) -> Result<Option<IpcMessage>, ConnectionError> {
+    Connection::from_env()?.send(message)
+}
+
+/// Subscribe to compositor events.
+///
+/// If `events` is empty, all events will be received.
+pub fn subscribe(events: Vec<EventKind>) -> Result<EventStream, ConnectionError> {
+    Connection::from_env()?.subscribe(events)
+}
+
+/// Connection to the Catacomb IPC socket.
+#[derive(Debug)]
+pub struct Connection {
+    reader: BufReader<UnixStream>,
+}
+
+impl Connection {
+    /// Connect to the IPC socket of the compositor running on
+    /// `WAYLAND_DISPLAY`.
+    pub fn from_env() -> Result<Self, ConnectionError> {
+        let socket_name =
+            env::var("WAYLAND_DISPLAY").map_err(|_| ConnectionError::MissingDisplay)?;
+        Self::connect(socket_path(&socket_name))
+    }
+
+    /// Connect to the IPC socket at the specified path.
+    pub fn connect(socket_path: impl AsRef<Path>) -> Result<Self, ConnectionError> {
+        let socket_path = socket_path.as_ref();
+
+        // Ensure Catacomb's IPC listener is running.
+        if !socket_path.exists() {
+            return Err(ConnectionError::SocketNotFound(socket_path.into()));
+        }
+
+        let stream = UnixStream::connect(socket_path)?;
 
-    let socket_path = socket_path(&socket_name);
+        Ok(Self { reader: BufReader::new(stream) })
+    }
+
+    /// Connect to the IPC socket, with a timeout for reading replies and
+    /// writing requests.
+    ///
+    /// Establishing the connection itself is not subject to the timeout.
+    pub fn connect_with_io_timeout(
+        socket_path: impl AsRef<Path>,
+        timeout: Duration,
+    ) -> Result<Self, ConnectionError> {
+        let connection = Self::connect(socket_path)?;
+        connection.set_timeout(Some(timeout))?;
+        Ok(connection)
+    }
+
+    /// Set the timeout for reading replies and writing requests.
+    ///
+    /// If `timeout` is `None`, socket operations will block indefinitely.
+    pub fn set_timeout(&self, timeout: Option<Duration>) -> Result<(), ConnectionError> {
+        let stream = self.reader.get_ref();
+        stream.set_read_timeout(timeout)?;
+        stream.set_write_timeout(timeout)?;
+        Ok(())
+    }
+
+    /// Send a message and wait for its reply.
+    ///
+    /// Returns `None` for requests which are only acknowledged, without
+    /// replying with any data.
+    pub fn send(&mut self, message: &IpcMessage) -> Result<Option<IpcMessage>, ConnectionError> {
+        self.write_message(message)?;
+        self.read_reply(message)
+    }
 
-    // Ensure Catacomb's IPC listener is running.
-    if !socket_path.exists() {
-        eprintln!("Error: IPC socket not found, ensure Catacomb is running");
-        process::exit(102);
+    /// Subscribe to compositor events.
+    ///
+    /// If `events` is empty, all events will be received.
+    pub fn subscribe(mut self, events: Vec<EventKind>) -> Result<EventStream, ConnectionError> {
+        self.send(&IpcMessage::Subscribe { events })?;
+        Ok(EventStream { reader: self.reader })
     }
 
-    let mut stream = UnixStream::connect(&socket_path)?;
+    /// Write a message to the socket.
+    fn write_message(&mut self, message: &IpcMessage) -> Result<(), ConnectionError> {
+        // Ensure IPC message is legal.
+        validate_message(message)
+            .map_err(|err| ConnectionError::InvalidMessage(err.to_string()))?;
 
-    // Write message to socket.
-    let json = serde_json::to_string(&message)?;
-    stream.write_all(json.as_bytes())?;
-    stream.flush()?;
+        let mut json = serde_json::to_string(&message)?;
+        json.push('\n');
 
-    // Shutdown write, to allow reading.
-    stream.shutdown(Shutdown::Write)?;
+        let mut stream = self.reader.get_ref();
+        stream.write_all(json.as_bytes())?;
+        stream.flush()?;
 
-    listen_for_reply(&stream, message)
+        Ok(())
+    }
+
+    /// Await message replies.
+    fn read_reply(&mut self, message: &IpcMessage) -> Result<Option<IpcMessage>, ConnectionError> {
+        // Read reply from buffer.
+        let mut buffer = String::new();
+        if self.reader.read_line(&mut buffer)? == 0 {
+            // Older compositors close the socket instead of acknowledging requests.
+            return if expects_reply(message) { Err(ConnectionError::Closed) } else { Ok(None) };
+        }
+
+        // Parse IPC reply.
+        let reply: IpcMessage = serde_json::from_str(&buffer)?;
+
+        match (message, reply) {
+            (_, IpcMessage::Error { kind, message }) => Err(IpcError { kind, message }.into()),
+            (_, IpcMessage::Ok) => Ok(None),
+            (IpcMessage::Dpms { .. }, reply @ IpcMessage::DpmsReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::Windows, reply @ IpcMessage::WindowsReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::ListBindings, reply @ IpcMessage::BindingsReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::ListGestures, reply @ IpcMessage::GesturesReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::ListTouchGestures, reply @ IpcMessage::TouchGesturesReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (IpcMessage::ListEdgeSwipes, reply @ IpcMessage::EdgeSwipesReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (IpcMessage::ListTapGestures, reply @ IpcMessage::TapGesturesReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (IpcMessage::ListScales, reply @ IpcMessage::ScalesReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::Version, reply @ IpcMessage::VersionReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::Outputs, reply @ IpcMessage::OutputsReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::ListRules, reply @ IpcMessage::RulesReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::InputConfig { .. }, reply @ IpcMessage::InputConfigReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (IpcMessage::Timings { .. }, reply @ IpcMessage::TimingsReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (IpcMessage::Theme { .. }, reply @ IpcMessage::ThemeReply { .. }) => Ok(Some(reply)),
+            (IpcMessage::ListIdleTimeouts, reply @ IpcMessage::IdleTimeoutsReply { .. }) => {
+                Ok(Some(reply))
+            },
+            (_, unexpected_reply) => {
+                Err(ConnectionError::UnexpectedReply(Box::new(unexpected_reply)))
+            },
+        }
+    }
+}
+
+/// Blocking iterator over IPC subscription events.
+#[derive(Debug)]
+pub struct EventStream {
+    reader: BufReader<UnixStream>,
 }
 
-/// Await message replies.
-fn listen_for_reply(
-    stream: &UnixStream,
-    message: &IpcMessage,
-) -> Result<Option<IpcMessage>, Box<dyn Error>> {
-    // Read reply from buffer.
-    let mut buffer = String::new();
-    let mut reader = BufReader::new(stream);
-    if let Ok(0) | Err(_) = reader.read_line(&mut buffer) {
-        return Ok(None);
+impl Iterator for EventStream {
+    type Item = Result<IpcEvent, ConnectionError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut buffer = String::new();
+        match self.reader.read_line(&mut buffer) {
+            Ok(0) => None,
+            Ok(_) => Some(serde_json::from_str(&buffer).map_err(Into::into)),
+            Err(err) => Some(Err(err.into())),
+        }
     }
+}
 
-    // Parse IPC reply.
-    let reply: IpcMessage = match serde_json::from_str(&buffer) {
-        Ok(reply) => reply,
-        Err(_) => return Ok(None),
-    };
+/// IPC connection errors.
+#[derive(Debug)]
+pub enum ConnectionError {
+    /// `WAYLAND_DISPLAY` is not set.
+    MissingDisplay,
+    /// IPC socket does not exist.
+    SocketNotFound(PathBuf),
+    /// Message was rejected before sending it.
+    InvalidMessage(String),
+    /// Compositor failed to process the request.
+    Request(IpcError),
+    /// Compositor replied with an unexpected message.
+    UnexpectedReply(Box<IpcMessage>),
+    /// Connection was closed before receiving a reply.
+    Closed,
+    /// Socket IO failed or timed out.
+    Io(io::Error),
+    /// Message (de)serialization failed.
+    Json(serde_json::Error),
+}
 
-    match (message, &reply) {
-        (IpcMessage::Dpms { .. }, IpcMessage::DpmsReply { .. }) => Ok(Some(reply)),
-        (IpcMessage::Dpms { .. }, unexpected_reply) => {
-            eprintln!("Error: Invalid IPC reply\n  {unexpected_reply:?}");
-            Ok(None)
-        },
-        _ => Ok(None),
+impl Display for ConnectionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingDisplay => write!(f, "WAYLAND_DISPLAY is not set"),
+            Self::SocketNotFound(path) => {
+                write!(f, "IPC socket {path:?} not found, ensure Catacomb is running")
+            },
+            Self::InvalidMessage(err) => write!(f, "invalid IPC message: {err}"),
+            Self::Request(err) => write!(f, "{err}"),
+            Self::UnexpectedReply(reply) => write!(f, "invalid IPC reply: {reply:?}"),
+            Self::Closed => write!(f, "IPC connection closed unexpectedly"),
+            Self::Io(err) => write!(f, "IPC socket error: {err}"),
+            Self::Json(err) => write!(f, "invalid IPC JSON: {err}"),
+        }
+    }
+}
+
+impl Error for ConnectionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Request(err) => Some(err),
+            Self::Io(err) => Some(err),
+            Self::Json(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<IpcError> for ConnectionError {
+    fn from(error: IpcError) -> Self {
+        Self::Request(error)
+    }
+}
+
+impl From<io::Error> for ConnectionError {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for ConnectionError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
     }
 }
 
@@ -534,15 +1891,70 @@ pub fn socket_path(socket_name: &str) -> PathBuf {
     dirs::runtime_dir().unwrap_or_else(env::temp_dir).join(format!("catacomb-{socket_name}.sock"))
 }
 
+/// Check if the compositor replies to a message with data.
+fn expects_reply(message: &IpcMessage) -> bool {
+    match message {
+        IpcMessage::Dpms { state } => state.is_none(),
+        IpcMessage::InputConfig { settings, .. } => settings == &InputSettings::default(),
+        IpcMessage::Timings { timings } => timings == &InputTimings::default(),
+        IpcMessage::Theme { theme } => theme == &ThemeSettings::default(),
+        IpcMessage::Windows
+        | IpcMessage::ListBindings
+        | IpcMessage::ListGestures
+        | IpcMessage::ListTouchGestures
+        | IpcMessage::ListEdgeSwipes
+        | IpcMessage::ListTapGestures
+        | IpcMessage::ListScales
+        | IpcMessage::Version
+        | IpcMessage::Outputs
+        | IpcMessage::ListRules
+        | IpcMessage::ListIdleTimeouts => true,
+        _ => false,
+    }
+}
+
 /// Validate a message beyond simple clap parsing.
 fn validate_message(message: &IpcMessage) -> Result<(), Box<dyn Error>> {
     match message {
         // Ensure App IDs are valid regexes.
         IpcMessage::Scale { app_id: Some(app_id), .. }
+        | IpcMessage::Window { app_id: Some(app_id), .. }
+        | IpcMessage::Rule { app_id, .. }
         | IpcMessage::BindGesture { app_id, .. }
+        | IpcMessage::BindTouchGesture { app_id, fingers: 2.., .. }
+        | IpcMessage::BindEdgeSwipe { app_id, .. }
+        | IpcMessage::BindTapGesture { app_id, .. }
         | IpcMessage::BindKey { app_id, .. } => {
             AppIdMatcher::try_from(app_id.clone())?;
         },
+        // Ensure touch gestures aren't used for single-finger input.
+        IpcMessage::BindTouchGesture { .. } => {
+            return Err("touch gestures require at least two fingers".into());
+        },
+        // Ensure window actions have a target.
+        IpcMessage::Window { app_id: None, id: None, .. } => {
+            return Err("window App ID or ID required".into());
+        },
+        // Ensure device settings have a target.
+        IpcMessage::InputConfig { device: None, settings }
+            if settings != &InputSettings::default() =>
+        {
+            return Err("input device name required".into());
+        },
+        // Ensure device settings are within their supported range.
+        IpcMessage::InputConfig { settings, .. } => settings.validate()?,
+        // Ensure encoder arguments are only used with an encoder.
+        IpcMessage::Record { pipe: false, arguments, .. } if !arguments.is_empty() => {
+            return Err("encoder arguments require --pipe".into());
+        },
+        // Ensure idle timeouts can't fire immediately.
+        IpcMessage::IdleTimeout { timeout: 0, .. } => {
+            return Err("idle timeout must be at least one second".into());
+        },
+        // Ensure timings are within their supported range.
+        IpcMessage::Timings { timings } => timings.validate()?,
+        // Ensure theme settings are within their supported range.
+        IpcMessage::Theme { theme } => theme.validate()?,
         // Ensure only fixed scales are used for global scale changes.
         IpcMessage::Scale { scale, app_id: None } if !matches!(scale, WindowScale::Fixed(_)) => {
             return Err(format!("global scale must be fixed, got \"{scale}\"").into());
@@ -556,3 +1968,224 @@ fn validate_message(message: &IpcMessage) -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_key_step() {
+        let step = KeyStep::from_str("q").unwrap();
+        assert_eq!(step, KeyStep { mods: Modifiers::default(), key: Keysym::Xkb(keysyms::KEY_q) });
+
+        let step = KeyStep::from_str("ctrl+shift+q").unwrap();
+        let mods = Modifiers { control: true, shift: true, ..Default::default() };
+        assert_eq!(step, KeyStep { mods, key: Keysym::Xkb(keysyms::KEY_q) });
+
+        assert!(KeyStep::from_str("hyper+q").is_err());
+        assert!(KeyStep::from_str("ctrl+").is_err());
+    }
+
+    #[test]
+    fn reply_mapping() {
+        let dpms = IpcMessage::Dpms { state: None };
+        let dpms_reply = IpcMessage::DpmsReply { state: CliToggle::On };
+        let reply = read_reply(&dpms, Some(&dpms_reply));
+        assert!(matches!(reply, Ok(Some(IpcMessage::DpmsReply { state: CliToggle::On }))));
+
+        let set_dpms = IpcMessage::Dpms { state: Some(CliToggle::Off) };
+        assert!(matches!(read_reply(&set_dpms, Some(&IpcMessage::Ok)), Ok(None)));
+
+        let unexpected = read_reply(&IpcMessage::Windows, Some(&dpms_reply));
+        assert!(matches!(unexpected, Err(ConnectionError::UnexpectedReply(_))));
+
+        let error = IpcMessage::Error { kind: ErrorKind::WindowNotFound, message: "none".into() };
+        let error = read_reply(&IpcMessage::Windows, Some(&error));
+        let kind = ErrorKind::WindowNotFound;
+        assert!(
+            matches!(error, Err(ConnectionError::Request(IpcError { kind: k, .. })) if k == kind)
+        );
+
+        let closed = read_reply(&IpcMessage::Windows, None);
+        assert!(matches!(closed, Err(ConnectionError::Closed)));
+
+        let closed = read_reply(&set_dpms, None);
+        assert!(matches!(closed, Ok(None)));
+    }
+
+    #[test]
+    fn validate_input_settings() {
+        let settings = InputSettings { accel_speed: Some(-1.), ..Default::default() };
+        assert_eq!(settings.validate(), Ok(()));
+
+        let settings = InputSettings { accel_speed: Some(1.5), ..Default::default() };
+        assert!(settings.validate().is_err());
+    }
+
+    #[test]
+    fn validate_timings() {
+        assert_eq!(InputTimings::default().validate(), Ok(()));
+
+        let timings =
+            InputTimings { repeat_interval: Some(25), friction: Some(1.), ..Default::default() };
+        assert_eq!(timings.validate(), Ok(()));
+
+        let timings = InputTimings { repeat_interval: Some(0), ..Default::default() };
+        assert!(timings.validate().is_err());
+
+        let timings = InputTimings { tap_distance: Some(-1.), ..Default::default() };
+        assert!(timings.validate().is_err());
+
+        let timings = InputTimings { friction: Some(1.5), ..Default::default() };
+        assert!(timings.validate().is_err());
+    }
+
+    #[test]
+    fn validate_theme() {
+        let theme = ThemeSettings { gesture_handle_height: Some(15), ..Default::default() };
+        assert_eq!(theme.validate(), Ok(()));
+
+        let theme = ThemeSettings { gesture_handle_height: Some(0), ..Default::default() };
+        assert!(theme.validate().is_err());
+
+        let theme = ThemeSettings { gesture_handle_height: Some(u32::MAX), ..Default::default() };
+        assert!(theme.validate().is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn requests_match_messages() {
+        let command = IpcMessage::augment_subcommands(clap::Command::new("msg"));
+        let mut subcommands: Vec<_> =
+            command.get_subcommands().map(|subcommand| subcommand.get_name().to_owned()).collect();
+        subcommands.sort();
+
+        // Convert request names to their kebab-case subcommand.
+        let mut requests: Vec<_> = REQUESTS
+            .iter()
+            .map(|request| {
+                request.chars().enumerate().fold(String::new(), |mut name, (i, c)| {
+                    if c.is_ascii_uppercase() && i > 0 {
+                        name.push('-');
+                    }
+                    name.push(c.to_ascii_lowercase());
+                    name
+                })
+            })
+            .collect();
+        requests.sort();
+
+        assert_eq!(requests, subcommands);
+    }
+
+    #[test]
+    fn merge_window_rules() {
+        let high = WindowRule {
+            scale: Some(WindowScale::Fixed(2.)),
+            orientation: Some(Orientation::Landscape),
+            ..Default::default()
+        };
+        let low = WindowRule {
+            scale: Some(WindowScale::Fixed(1.)),
+            orientation: Some(Orientation::Portrait),
+            placement: Some(LayoutSlot::Secondary),
+            fullscreen: Some(true),
+            no_thumbnail: Some(true),
+            ..Default::default()
+        };
+
+        assert_eq!(high.merge(&low), WindowRule {
+            scale: Some(WindowScale::Fixed(2.)),
+            orientation: Some(Orientation::Landscape),
+            placement: Some(LayoutSlot::Secondary),
+            fullscreen: Some(true),
+            deny_focus: None,
+            no_thumbnail: Some(true),
+        });
+
+        // Specific rules can disable options of generic rules.
+        let high = WindowRule { fullscreen: Some(false), ..Default::default() };
+        assert_eq!(high.merge(&low).fullscreen, Some(false));
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_rule_flags() {
+        #[derive(clap::Parser)]
+        struct Msg {
+            #[clap(subcommand)]
+            message: IpcMessage,
+        }
+
+        let args = ["msg", "rule", "--fullscreen", "--deny-focus=false", "mpv"];
+        let message = <Msg as clap::Parser>::try_parse_from(args).unwrap().message;
+        assert!(matches!(message, IpcMessage::Rule {
+            fullscreen: Some(true),
+            deny_focus: Some(false),
+            no_thumbnail: None,
+            ..
+        }));
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_color() {
+        assert_eq!(Color::from_str("#ff8000").unwrap(), Color([255, 128, 0, 255]));
+        assert_eq!(Color::from_str("ff800080").unwrap(), Color([255, 128, 0, 128]));
+
+        assert!(Color::from_str("#ff80").is_err());
+        assert!(Color::from_str("#gg8000").is_err());
+        assert!(Color::from_str("#ff8€").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_geometry() {
+        let geometry = Geometry::from_str("10,-20 300x400").unwrap();
+        assert_eq!(geometry, Geometry { x: 10, y: -20, width: 300, height: 400 });
+
+        assert!(Geometry::from_str("10,20").is_err());
+        assert!(Geometry::from_str("10 300x400").is_err());
+        assert!(Geometry::from_str("10,20 300x0").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_key_trigger() {
+        assert_eq!(KeyTrigger::from_str("Press").unwrap(), KeyTrigger::Press);
+        assert_eq!(KeyTrigger::from_str("release").unwrap(), KeyTrigger::Release);
+        assert_eq!(KeyTrigger::from_str("hold=500").unwrap(), KeyTrigger::Hold(500));
+
+        assert!(KeyTrigger::from_str("hold").is_err());
+        assert!(KeyTrigger::from_str("hold=-1").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "clap")]
+    fn parse_tap_location() {
+        assert_eq!(TapLocation::from_str("Handle").unwrap(), TapLocation::Handle);
+        let top_left = TapLocation::Sector(GestureSector::TopLeft);
+        assert_eq!(TapLocation::from_str("top-left").unwrap(), top_left);
+        assert_eq!(TapLocation::from_str("tl").unwrap(), top_left);
+
+        assert!(TapLocation::from_str("center").is_err());
+    }
+
+    /// Read the reply to `message`, with the compositor sending `reply`.
+    fn read_reply(
+        message: &IpcMessage,
+        reply: Option<&IpcMessage>,
+    ) -> Result<Option<IpcMessage>, ConnectionError> {
+        let (client, mut server) = UnixStream::pair().unwrap();
+        if let Some(reply) = reply {
+            let mut json = serde_json::to_string(reply).unwrap();
+            json.push('\n');
+            server.write_all(json.as_bytes()).unwrap();
+        }
+        drop(server);
+
+        let mut connection = Connection { reader: BufReader::new(client) };
+        connection.read_reply(message)
+    }
+}
@@ -84,11 +84,13 @@ use smithay::{
 };
 use tracing::{error, info};
 
-use crate::config::KeyBinding;
-use crate::drawing::CatacombSurfaceData;
-use crate::input::{REPEAT_DELAY, REPEAT_RATE, TouchState};
+use crate::config::{Config, KeyBinding};
+use crate::drawing::{CatacombSurfaceData, Theme};
+use crate::idle::IdleManager;
+use crate::input::{Timings, TouchState};
+use crate::ipc_server::IpcSubscribers;
 use crate::orientation::{Accelerometer, AccelerometerSource};
-use crate::output::Canvas;
+use crate::output::{Canvas, GESTURE_HANDLE_HEIGHT};
 use crate::protocols::screencopy::frame::Screencopy;
 use crate::protocols::screencopy::{ScreencopyHandler, ScreencopyManagerState};
 use crate::udev::Udev;
@@ -122,6 +124,8 @@ pub struct Catacomb {
     pub seat: Seat<Self>,
     pub terminated: bool,
     pub backend: Udev,
+    pub ipc_subscribers: IpcSubscribers,
+    pub idle_manager: IdleManager,
 
     // Smithay state.
     pub idle_notifier_state: IdleNotifierState<Self>,
@@ -259,8 +263,7 @@ impl Catacomb {
 
         // Initialize keyboard/touch/data device.
         let data_device_state = DataDeviceState::new::<Self>(&display_handle);
-        let repeat_delay = REPEAT_DELAY.as_millis() as i32;
-        let repeat_rate = REPEAT_RATE.as_millis() as i32;
+        let (repeat_delay, repeat_rate) = Timings::default().keyboard_repeat_info();
         seat.add_keyboard(XkbConfig::default(), repeat_delay, repeat_rate)
             .expect("adding keyboard");
 
@@ -274,6 +277,9 @@ impl Catacomb {
         seat.add_touch();
         let touch_state = TouchState::new(event_loop.clone());
 
+        // Initialize built-in idle timeouts.
+        let idle_manager = IdleManager::new(event_loop.clone());
+
         // Start IPC socket listener.
         ipc_server::spawn_ipc_socket(&event_loop, &socket_name).expect("spawn IPC socket");
 
@@ -291,31 +297,9 @@ impl Catacomb {
         }
 
         // Run user startup script.
-        let user_config = dirs::config_dir().map(|dir| dir.join("catacomb").join(INIT_EXEC));
-        match user_config {
-            Some(user_config) if user_config.exists() => {
-                info!("Loading user config at {:?}", user_config);
-
-                if let Err(err) = crate::daemon(user_config.as_os_str(), []) {
-                    error!("Unable to launch {user_config:?}: {err}");
-                }
-            },
-            _ => {
-                let global_path = format!("/etc/catacomb/{INIT_EXEC}");
-                let global_path = Path::new(&global_path);
-                if global_path.exists() {
-                    info!("Loading global config at {:?}", global_path);
+        Self::run_initrc(false);
 
-                    if let Err(err) = crate::daemon(global_path.as_os_str(), []) {
-                        error!("Unable to launch {global_path:?}: {err}");
-                    }
-                } else {
-                    info!("No configuration script found");
-                }
-            },
-        }
-
-        Self {
+        let mut catacomb = Self {
             keyboard_shortcuts_inhibit_state,
             primary_selection_state,
             xdg_activation_state,
@@ -328,6 +312,7 @@ impl Catacomb {
             xdg_shell_state,
             display_handle,
             dmabuf_state,
+            idle_manager,
             touch_state,
             event_loop,
             lock_state,
@@ -340,6 +325,7 @@ impl Catacomb {
             accelerometer_token: accel_token,
             display_on: true,
             last_cursor_position: Default::default(),
+            ipc_subscribers: Default::default(),
             idle_inhibitors: Default::default(),
             key_bindings: Default::default(),
             ime_override: Default::default(),
@@ -349,6 +335,79 @@ impl Catacomb {
             terminated: Default::default(),
             stalled: Default::default(),
             locker: Default::default(),
+        };
+
+        // Apply declarative user config.
+        catacomb.load_config();
+
+        catacomb
+    }
+
+    /// Reset user configuration and re-apply all config files.
+    pub fn reload_config(&mut self) {
+        self.key_bindings.clear();
+        self.touch_state.user_gestures.clear();
+        self.touch_state.touch_gestures.clear();
+        self.touch_state.edge_swipes.clear();
+        self.touch_state.tap_gestures.clear();
+        self.windows.clear_window_rules();
+        self.set_xkb_config(XkbConfig::default());
+        self.set_timings(Timings::default());
+        self.backend.set_theme(Theme::default());
+        self.windows.set_gesture_handle_height(GESTURE_HANDLE_HEIGHT);
+        self.idle_manager.clear();
+        self.backend.reset_input_settings();
+        self.lock_orientation(Some(Orientation::default()));
+        self.draw_cursor = false;
+        self.force_redraw(false);
+
+        self.load_config();
+        Self::run_initrc(true);
+    }
+
+    /// Run the user's startup script.
+    ///
+    /// The `CATACOMB_RELOAD` environment variable is set for the script when
+    /// the config is reloaded, to allow skipping long-running daemons.
+    fn run_initrc(reload: bool) {
+        let envs: &[_] = if reload { &[("CATACOMB_RELOAD", "1")] } else { &[] };
+
+        let user_config = dirs::config_dir().map(|dir| dir.join("catacomb").join(INIT_EXEC));
+        match user_config {
+            Some(user_config) if user_config.exists() => {
+                info!("Loading user config at {:?}", user_config);
+
+                if let Err(err) = crate::daemon(user_config.as_os_str(), [], envs) {
+                    error!("Unable to launch {user_config:?}: {err}");
+                }
+            },
+            _ => {
+                let global_path = format!("/etc/catacomb/{INIT_EXEC}");
+                let global_path = Path::new(&global_path);
+                if global_path.exists() {
+                    info!("Loading global config at {:?}", global_path);
+
+                    if let Err(err) = crate::daemon(global_path.as_os_str(), [], envs) {
+                        error!("Unable to launch {global_path:?}: {err}");
+                    }
+                } else {
+                    info!("No configuration script found");
+                }
+            },
+        }
+    }
+
+    /// Apply the declarative configuration file.
+    fn load_config(&mut self) {
+        let config = match Config::load() {
+            Some(config) => config,
+            None => return,
+        };
+
+        for message in config.into_messages() {
+            if let Err(err) = ipc_server::handle_request(message, self) {
+                error!("Invalid config entry: {err}");
+            }
         }
     }
 
@@ -385,12 +444,14 @@ impl Catacomb {
         if focus != self.last_focus {
             self.last_focus.clone_from(&focus);
             self.focus(focus);
+            self.ipc_subscribers.mark_dirty();
         }
 
         // Update idle inhibition state.
         let mut inhibitors = self.idle_inhibitors.iter();
         let inhibited = inhibitors.any(|surface| self.windows.surface_visible(surface));
         self.idle_notifier_state.set_is_inhibited(inhibited);
+        self.idle_manager.set_inhibited(inhibited);
 
         // Check whether touch cursor should be drawn.
         let cursor_position = self.touch_state.position().filter(|_| self.draw_cursor);
@@ -436,6 +497,9 @@ impl Catacomb {
 
     /// Focus a new surface.
     fn focus(&mut self, surface: Option<WlSurface>) {
+        // Apply orientation rules of the newly focused window.
+        self.windows.update_forced_orientation();
+
         if let Some(keyboard) = self.seat.get_keyboard() {
             keyboard.set_focus(self, surface, SERIAL_COUNTER.next_serial());
         }
@@ -471,12 +535,14 @@ impl Catacomb {
     /// Set output power mode.
     pub fn set_display_status(&mut self, on: bool) {
         self.display_on = on;
+        self.idle_manager.display_off = false;
+        self.ipc_subscribers.mark_dirty();
 
         // Pause accelerometer checks while display is off.
         if on {
             // Count wakeup as activity, to ensure that clients like wayidle receive a
             // resume and new idle events after waking the monitor back up.
-            self.idle_notifier_state.notify_activity(&self.seat);
+            self.notify_activity();
 
             if !self.windows.orientation_locked() {
                 trace_error!(self.event_loop.enable(&self.accelerometer_token));
@@ -638,6 +704,10 @@ impl XdgShellHandler for Catacomb {
         self.windows.unfullscreen(&surface);
     }
 
+    fn title_changed(&mut self, _surface: ToplevelSurface) {
+        self.ipc_subscribers.mark_dirty();
+    }
+
     fn toplevel_destroyed(&mut self, surface: ToplevelSurface) {
         self.windows.reap_xdg(&surface);
         self.unstall();
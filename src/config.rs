@@ -1,6 +1,246 @@
 //! Compositor configuration.
 
-use catacomb_ipc::{AppIdMatcher, GestureSector, KeyTrigger, Keysym, Modifiers};
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{fs, iter};
+
+use catacomb_ipc::{
+    AppIdMatcher, CliToggle, EdgeSwipeBindingInfo, GestureAction, GestureBindingInfo,
+    GestureSector, IpcMessage, KeyBindingInfo, KeyStep, KeyTrigger, Keysym, LayoutSlot, Modifiers,
+    Orientation, ScreenEdge, SwipeDirection, TapGesture, TapGestureBindingInfo, TapLocation,
+    TouchGesture, TouchGestureBindingInfo, WindowScale,
+};
+use serde::{Deserialize, Deserializer, de};
+use smithay::utils::{Logical, Point, Size};
+use tracing::{error, info};
+
+/// Declarative configuration file name.
+const CONFIG_FILE: &str = "config.json";
+
+/// Default minimum distance for edge swipes.
+pub const DEFAULT_EDGE_SWIPE_DISTANCE: u32 = 100;
+
+/// Width of the area at the screen edges where edge swipes can start.
+const EDGE_SWIPE_WIDTH: f64 = 20.;
+
+/// Default maximum time between key presses of a key sequence.
+pub const DEFAULT_SEQUENCE_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Declarative compositor configuration.
+#[derive(Deserialize, Default, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub key_bindings: Vec<KeyBindingConfig>,
+    pub gesture_bindings: Vec<GestureBindingConfig>,
+    pub window_scales: Vec<WindowScaleConfig>,
+    pub window_rules: Vec<WindowRuleConfig>,
+    pub keyboard: Option<KeyboardConfig>,
+    /// Locked output orientation.
+    pub orientation: Option<Orientation>,
+    /// Touch cursor visibility.
+    pub cursor: Option<bool>,
+}
+
+impl Config {
+    /// Load the configuration file from the user's config directory.
+    pub fn load() -> Option<Self> {
+        let path = dirs::config_dir()?.join("catacomb").join(CONFIG_FILE);
+
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == ErrorKind::NotFound => return None,
+            Err(err) => {
+                error!("Unable to read config at {path:?}: {err}");
+                return None;
+            },
+        };
+
+        match serde_json::from_str(&content) {
+            Ok(config) => {
+                info!("Loading declarative config at {path:?}");
+                Some(config)
+            },
+            Err(err) => {
+                error!("Invalid config at {path:?}: {err}");
+                None
+            },
+        }
+    }
+
+    /// Convert the configuration to its equivalent IPC messages.
+    pub fn into_messages(self) -> Vec<IpcMessage> {
+        let mut messages = Vec::new();
+
+        for binding in self.key_bindings {
+            messages.push(IpcMessage::BindKey {
+                app_id: binding.app_id,
+                mods: Some(binding.mods),
+                trigger: binding.trigger,
+                sequence: binding.sequence,
+                sequence_timeout: binding.sequence_timeout,
+                key: binding.key,
+                program: binding.program,
+                arguments: binding.arguments,
+            });
+        }
+
+        for gesture in self.gesture_bindings {
+            let GestureBindingConfig { app_id, start, end, action } = gesture;
+            messages.push(match action {
+                GestureActionConfig::Cmd { program, arguments } => {
+                    IpcMessage::BindGesture { app_id, start, end, program, arguments }
+                },
+                GestureActionConfig::Key { mods, key } => {
+                    IpcMessage::BindGestureKey { app_id, start, end, mods: Some(mods), key }
+                },
+            });
+        }
+
+        for WindowScaleConfig { app_id, scale } in self.window_scales {
+            messages.push(IpcMessage::Scale { scale, app_id: Some(app_id) });
+        }
+
+        for rule in self.window_rules {
+            let WindowRuleConfig {
+                app_id,
+                fullscreen,
+                deny_focus,
+                orientation,
+                no_thumbnail,
+                placement,
+            } = rule;
+            messages.push(IpcMessage::Rule {
+                app_id,
+                fullscreen,
+                deny_focus,
+                orientation,
+                no_thumbnail,
+                placement,
+            });
+        }
+
+        if let Some(KeyboardConfig { layout, model, options, variant }) = self.keyboard {
+            messages.push(IpcMessage::KeyboardConfig { layout, model, options, variant });
+        }
+
+        if let Some(orientation) = self.orientation {
+            messages.push(IpcMessage::Orientation { lock: Some(orientation), unlock: false });
+        }
+
+        if let Some(cursor) = self.cursor {
+            let state = if cursor { CliToggle::On } else { CliToggle::Off };
+            messages.push(IpcMessage::Cursor { state });
+        }
+
+        messages
+    }
+}
+
+/// Key binding configuration.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct KeyBindingConfig {
+    app_id: String,
+    #[serde(default, deserialize_with = "from_str")]
+    mods: Modifiers,
+    #[serde(deserialize_with = "from_str")]
+    key: Keysym,
+    #[serde(default = "default_trigger")]
+    trigger: KeyTrigger,
+    #[serde(default, deserialize_with = "from_str_vec")]
+    sequence: Vec<KeyStep>,
+    sequence_timeout: Option<u64>,
+    program: String,
+    #[serde(default)]
+    arguments: Vec<String>,
+}
+
+/// Gesture binding configuration.
+#[derive(Deserialize, Debug)]
+pub struct GestureBindingConfig {
+    app_id: String,
+    start: GestureSector,
+    end: GestureSector,
+    #[serde(flatten)]
+    action: GestureActionConfig,
+}
+
+/// Gesture binding action configuration.
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum GestureActionConfig {
+    Cmd {
+        program: String,
+        #[serde(default)]
+        arguments: Vec<String>,
+    },
+    Key {
+        #[serde(default, deserialize_with = "from_str")]
+        mods: Modifiers,
+        #[serde(deserialize_with = "from_str")]
+        key: Keysym,
+    },
+}
+
+/// Per-window scale configuration.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WindowScaleConfig {
+    app_id: String,
+    #[serde(deserialize_with = "from_str")]
+    scale: WindowScale,
+}
+
+/// Per-app window rule configuration.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct WindowRuleConfig {
+    app_id: String,
+    fullscreen: Option<bool>,
+    deny_focus: Option<bool>,
+    orientation: Option<Orientation>,
+    no_thumbnail: Option<bool>,
+    placement: Option<LayoutSlot>,
+}
+
+/// Keyboard configuration.
+#[derive(Deserialize, Debug)]
+#[serde(deny_unknown_fields)]
+pub struct KeyboardConfig {
+    layout: Option<String>,
+    model: Option<String>,
+    options: Option<String>,
+    variant: Option<String>,
+}
+
+/// Deserialize a value from its command line string representation.
+fn from_str<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map_err(de::Error::custom)
+}
+
+/// Deserialize a list of values from their command line string representation.
+fn from_str_vec<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr,
+    T::Err: Display,
+{
+    let texts = Vec::<String>::deserialize(deserializer)?;
+    texts.iter().map(|text| text.parse().map_err(de::Error::custom)).collect()
+}
+
+/// Default trigger for key bindings.
+fn default_trigger() -> KeyTrigger {
+    KeyTrigger::Press
+}
 
 /// User-defined gesture action.
 #[derive(Debug)]
@@ -11,6 +251,149 @@ pub struct GestureBinding {
     pub action: GestureBindingAction,
 }
 
+impl GestureBinding {
+    /// Get the IPC representation of this binding.
+    pub fn info(&self) -> GestureBindingInfo {
+        let action = match &self.action {
+            GestureBindingAction::Cmd((program, arguments)) => {
+                GestureAction::Cmd { program: program.clone(), arguments: arguments.clone() }
+            },
+            GestureBindingAction::Key((key, mods)) => {
+                GestureAction::Key { mods: *mods, key: Keysym::Xkb(*key) }
+            },
+        };
+
+        GestureBindingInfo {
+            app_id: self.app_id.base().into(),
+            start: self.start,
+            end: self.end,
+            action,
+        }
+    }
+}
+
+/// User-defined multi-finger touch gesture action.
+#[derive(Debug)]
+pub struct TouchGestureBinding {
+    pub app_id: AppIdMatcher,
+    pub fingers: u8,
+    pub gesture: TouchGesture,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+impl TouchGestureBinding {
+    /// Get the IPC representation of this binding.
+    pub fn info(&self) -> TouchGestureBindingInfo {
+        TouchGestureBindingInfo {
+            app_id: self.app_id.base().into(),
+            fingers: self.fingers,
+            gesture: self.gesture,
+            program: self.program.clone(),
+            arguments: self.arguments.clone(),
+        }
+    }
+}
+
+/// User-defined long-press or double-tap gesture action.
+#[derive(Debug)]
+pub struct TapGestureBinding {
+    pub app_id: AppIdMatcher,
+    pub gesture: TapGesture,
+    pub location: TapLocation,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+impl TapGestureBinding {
+    /// Get the IPC representation of this binding.
+    pub fn info(&self) -> TapGestureBindingInfo {
+        TapGestureBindingInfo {
+            app_id: self.app_id.base().into(),
+            gesture: self.gesture,
+            location: self.location,
+            program: self.program.clone(),
+            arguments: self.arguments.clone(),
+        }
+    }
+}
+
+/// User-defined edge swipe gesture action.
+#[derive(Debug)]
+pub struct EdgeSwipeBinding {
+    pub app_id: AppIdMatcher,
+    pub edge: ScreenEdge,
+    pub direction: SwipeDirection,
+    pub min_distance: u32,
+    pub min_velocity: Option<u32>,
+    pub program: String,
+    pub arguments: Vec<String>,
+}
+
+impl EdgeSwipeBinding {
+    /// Get the IPC representation of this binding.
+    pub fn info(&self) -> EdgeSwipeBindingInfo {
+        EdgeSwipeBindingInfo {
+            app_id: self.app_id.base().into(),
+            edge: self.edge,
+            direction: self.direction,
+            min_distance: self.min_distance,
+            min_velocity: self.min_velocity,
+            program: self.program.clone(),
+            arguments: self.arguments.clone(),
+        }
+    }
+
+    /// Check if a touch position is within this binding's edge.
+    ///
+    /// The `output_size` should exclude the gesture handle, so swipes from the
+    /// bottom edge start directly above it.
+    pub fn starts_at(&self, output_size: Size<f64, Logical>, point: Point<f64, Logical>) -> bool {
+        // Ignore touches on the gesture handle.
+        if point.y >= output_size.h {
+            return false;
+        }
+
+        match self.edge {
+            ScreenEdge::Top => point.y < EDGE_SWIPE_WIDTH,
+            ScreenEdge::Bottom => point.y >= output_size.h - EDGE_SWIPE_WIDTH,
+            ScreenEdge::Left => point.x < EDGE_SWIPE_WIDTH,
+            ScreenEdge::Right => point.x >= output_size.w - EDGE_SWIPE_WIDTH,
+        }
+    }
+
+    /// Check if a completed swipe matches this binding.
+    pub fn matches(
+        &self,
+        output_size: Size<f64, Logical>,
+        start: Point<f64, Logical>,
+        end: Point<f64, Logical>,
+        duration: Duration,
+    ) -> bool {
+        if !self.starts_at(output_size, start) {
+            return false;
+        }
+
+        // Get distance traveled in the swipe's direction.
+        let delta = end - start;
+        let (distance, cross_distance) = match self.direction {
+            SwipeDirection::Up => (-delta.y, delta.x),
+            SwipeDirection::Down => (delta.y, delta.x),
+            SwipeDirection::Left => (-delta.x, delta.y),
+            SwipeDirection::Right => (delta.x, delta.y),
+        };
+
+        // Ensure the swipe mostly moved in the desired direction.
+        if distance < self.min_distance as f64 || distance < cross_distance.abs() {
+            return false;
+        }
+
+        // Ensure the swipe was fast enough.
+        self.min_velocity
+            .is_none_or(|min_velocity| distance / duration.as_secs_f64() >= min_velocity as f64)
+    }
+}
+
 /// Action variants for gesture bindings.
 #[derive(Clone, Debug)]
 pub enum GestureBindingAction {
@@ -27,4 +410,187 @@ pub struct KeyBinding {
     pub program: String,
     pub arguments: Vec<String>,
     pub trigger: KeyTrigger,
+    pub sequence: Vec<KeyStep>,
+    pub sequence_timeout: Duration,
+}
+
+impl KeyBinding {
+    /// Get the IPC representation of this binding.
+    pub fn info(&self) -> KeyBindingInfo {
+        KeyBindingInfo {
+            app_id: self.app_id.base().into(),
+            mods: self.mods,
+            key: self.key,
+            trigger: self.trigger,
+            sequence: self.sequence.clone(),
+            sequence_timeout: self.sequence_timeout.as_millis() as u64,
+            program: self.program.clone(),
+            arguments: self.arguments.clone(),
+        }
+    }
+
+    /// Get the last key of this binding's sequence.
+    pub fn last_step(&self) -> KeyStep {
+        self.sequence.last().copied().unwrap_or(KeyStep { mods: self.mods, key: self.key })
+    }
+
+    /// Check if the most recent key presses complete this binding's sequence.
+    pub fn sequence_completed(&self, key_presses: &[(Instant, KeyStep)]) -> bool {
+        let len = self.sequence.len() + 1;
+        if key_presses.len() < len {
+            return false;
+        }
+        let key_presses = &key_presses[key_presses.len() - len..];
+
+        // Ensure all keys were pressed in the correct order.
+        let first = KeyStep { mods: self.mods, key: self.key };
+        let mut steps = iter::once(&first).chain(&self.sequence);
+        if !key_presses.iter().all(|(_, step)| Some(step) == steps.next()) {
+            return false;
+        }
+
+        // Ensure no key press exceeded the sequence timeout.
+        key_presses.windows(2).all(|presses| presses[1].0 - presses[0].0 <= self.sequence_timeout)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use smithay::input::keyboard::keysyms;
+
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config: Config = serde_json::from_str(
+            r#"{
+                "key_bindings": [
+                    {
+                        "app_id": "*",
+                        "mods": "ctrl",
+                        "key": "q",
+                        "trigger": { "Hold": 500 },
+                        "program": "true"
+                    }
+                ],
+                "gesture_bindings": [
+                    { "app_id": "*", "start": "BottomLeft", "end": "TopLeft", "key": "XF86Back" }
+                ],
+                "window_scales": [{ "app_id": "firefox", "scale": "*2" }],
+                "orientation": "landscape",
+                "cursor": true
+            }"#,
+        )
+        .unwrap();
+
+        let messages = config.into_messages();
+        assert_eq!(messages.len(), 5);
+        assert!(matches!(&messages[0], IpcMessage::BindKey {
+            mods: Some(Modifiers { control: true, shift: false, .. }),
+            key: Keysym::Xkb(keysyms::KEY_q),
+            trigger: KeyTrigger::Hold(500),
+            ..
+        }));
+        assert!(matches!(&messages[1], IpcMessage::BindGestureKey {
+            start: GestureSector::BottomLeft,
+            end: GestureSector::TopLeft,
+            ..
+        }));
+        assert!(matches!(&messages[2], IpcMessage::Scale {
+            scale: WindowScale::Multiplicative(scale),
+            ..
+        } if *scale == 2.));
+        assert!(matches!(&messages[3], IpcMessage::Orientation {
+            lock: Some(Orientation::Landscape),
+            unlock: false
+        }));
+        assert!(matches!(&messages[4], IpcMessage::Cursor { state: CliToggle::On }));
+    }
+
+    #[test]
+    fn reject_unknown_config_fields() {
+        assert!(serde_json::from_str::<Config>(r#"{ "cursors": true }"#).is_err());
+
+        let binding =
+            r#"{ "key_bindings": [{ "app_id": "*", "key": "q", "program": "true", "args": [] }] }"#;
+        assert!(serde_json::from_str::<Config>(binding).is_err());
+
+        assert!(serde_json::from_str::<Config>("{}").is_ok());
+    }
+
+    #[test]
+    fn key_sequence_completed() {
+        let ctrl = Modifiers { control: true, ..Default::default() };
+        let step = |mods, key| KeyStep { mods, key: Keysym::Xkb(key) };
+        let binding = KeyBinding {
+            app_id: AppIdMatcher::try_from(String::from("*")).unwrap(),
+            mods: ctrl,
+            key: Keysym::Xkb(keysyms::KEY_a),
+            program: String::new(),
+            arguments: Vec::new(),
+            trigger: KeyTrigger::Press,
+            sequence: vec![step(ctrl, keysyms::KEY_q)],
+            sequence_timeout: DEFAULT_SEQUENCE_TIMEOUT,
+        };
+
+        let now = Instant::now();
+        let later = now + Duration::from_millis(100);
+
+        // Modified follow-up step.
+        let presses = [(now, step(ctrl, keysyms::KEY_a)), (later, step(ctrl, keysyms::KEY_q))];
+        assert!(binding.sequence_completed(&presses));
+
+        // Missing modifier.
+        let presses =
+            [(now, step(ctrl, keysyms::KEY_a)), (later, step(Default::default(), keysyms::KEY_q))];
+        assert!(!binding.sequence_completed(&presses));
+
+        // Wrong order.
+        let presses = [(now, step(ctrl, keysyms::KEY_q)), (later, step(ctrl, keysyms::KEY_a))];
+        assert!(!binding.sequence_completed(&presses));
+
+        // Incomplete sequence.
+        assert!(!binding.sequence_completed(&[(later, step(ctrl, keysyms::KEY_q))]));
+
+        // Exceeded sequence timeout.
+        let too_late = now + DEFAULT_SEQUENCE_TIMEOUT * 2;
+        let presses = [(now, step(ctrl, keysyms::KEY_a)), (too_late, step(ctrl, keysyms::KEY_q))];
+        assert!(!binding.sequence_completed(&presses));
+    }
+
+    #[test]
+    fn edge_swipe_matches() {
+        let binding = EdgeSwipeBinding {
+            app_id: AppIdMatcher::try_from(String::from("*")).unwrap(),
+            edge: ScreenEdge::Bottom,
+            direction: SwipeDirection::Up,
+            min_distance: 100,
+            min_velocity: Some(500),
+            program: String::new(),
+            arguments: Vec::new(),
+        };
+        let output_size = Size::from((1000., 2000.));
+        let fast = Duration::from_millis(100);
+
+        assert!(binding.starts_at(output_size, Point::from((500., 1990.))));
+        assert!(!binding.starts_at(output_size, Point::from((500., 1900.))));
+        assert!(!binding.starts_at(output_size, Point::from((500., 2005.))));
+
+        let start = Point::from((500., 1990.));
+        assert!(binding.matches(output_size, start, Point::from((520., 1800.)), fast));
+
+        // Too short.
+        assert!(!binding.matches(output_size, start, Point::from((500., 1950.)), fast));
+
+        // Mostly sideways.
+        assert!(!binding.matches(output_size, start, Point::from((800., 1850.)), fast));
+
+        // Too slow.
+        let slow = Duration::from_secs(1);
+        assert!(!binding.matches(output_size, start, Point::from((520., 1800.)), slow));
+
+        // Not starting at the edge.
+        let start = Point::from((500., 1000.));
+        assert!(!binding.matches(output_size, start, Point::from((500., 500.)), fast));
+    }
 }
@@ -4,7 +4,7 @@ use std::cell::Cell;
 use std::ops::Deref;
 use std::rc::Rc;
 
-use catacomb_ipc::WindowScale;
+use catacomb_ipc::{Color, ThemeSettings, WindowScale};
 use smithay::backend::renderer::element::utils::{
     CropRenderElement, Relocate, RelocateRenderElement, RescaleRenderElement,
 };
@@ -24,7 +24,7 @@ use smithay::wayland::compositor::{
 use smithay::wayland::viewporter::{self, ViewportCachedState};
 
 use crate::geometry::SubtractRectFast;
-use crate::output::{Canvas, GESTURE_HANDLE_HEIGHT};
+use crate::output::Canvas;
 
 /// Color of the hovered overview tiling location highlight.
 const ACTIVE_DROP_TARGET_RGBA: [u8; 4] = [64, 64, 64, 128];
@@ -35,17 +35,26 @@ const URGENCY_ICON_RGBA: [u8; 4] = [172, 66, 66, 255];
 /// Color of the overview tiling location highlight.
 const DROP_TARGET_RGBA: [u8; 4] = [32, 32, 32, 64];
 
+/// Color of overview windows with hidden content.
+const THUMBNAIL_PLACEHOLDER_RGBA: [u8; 4] = [48, 48, 48, 255];
+
 /// Relative size of gesture notch to the handle's whole width/height.
 const GESTURE_NOTCH_PERCENTAGE: f64 = 0.2;
 
 /// Gesture handle color with automatic IME control.
-const GESTURE_HANDLE_DEFAULT_RGB: [u8; 3] = [255; 3];
+const GESTURE_HANDLE_DEFAULT_RGBA: [u8; 4] = [255; 4];
 
 /// Gesture handle color with IME force-enabled.
-const GESTURE_HANDLE_LOCKED_RGB: [u8; 3] = [42, 117, 42];
+const GESTURE_HANDLE_LOCKED_RGBA: [u8; 4] = [42, 117, 42, 255];
 
 /// Gesture handle color with IME force-disabled.
-const GESTURE_HANDLE_BLOCKED_RGB: [u8; 3] = [117, 42, 42];
+const GESTURE_HANDLE_BLOCKED_RGBA: [u8; 4] = [117, 42, 42, 255];
+
+/// Gesture handle color outside of the notch.
+const GESTURE_HANDLE_BACKGROUND_RGBA: [u8; 4] = [0, 0, 0, 255];
+
+/// Color behind all windows.
+const BACKGROUND_RGBA: [u8; 4] = [0, 0, 0, 255];
 
 /// Color of the touch cursor.
 const CURSOR_RGBA: [u8; 4] = [86, 33, 33, 192];
@@ -434,10 +443,89 @@ impl RenderElement<GlesRenderer> for CatacombElement {
     }
 }
 
+/// Compositor colors.
+#[derive(Copy, Clone, Debug)]
+pub struct Theme {
+    pub gesture_handle_default: [u8; 4],
+    pub gesture_handle_locked: [u8; 4],
+    pub gesture_handle_blocked: [u8; 4],
+    pub gesture_handle_background: [u8; 4],
+    pub cursor: [u8; 4],
+    pub urgency_icon: [u8; 4],
+    pub drop_target: [u8; 4],
+    pub active_drop_target: [u8; 4],
+    pub thumbnail_placeholder: [u8; 4],
+    pub background: [u8; 4],
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            gesture_handle_default: GESTURE_HANDLE_DEFAULT_RGBA,
+            gesture_handle_locked: GESTURE_HANDLE_LOCKED_RGBA,
+            gesture_handle_blocked: GESTURE_HANDLE_BLOCKED_RGBA,
+            gesture_handle_background: GESTURE_HANDLE_BACKGROUND_RGBA,
+            cursor: CURSOR_RGBA,
+            urgency_icon: URGENCY_ICON_RGBA,
+            drop_target: DROP_TARGET_RGBA,
+            active_drop_target: ACTIVE_DROP_TARGET_RGBA,
+            thumbnail_placeholder: THUMBNAIL_PLACEHOLDER_RGBA,
+            background: BACKGROUND_RGBA,
+        }
+    }
+}
+
+impl Theme {
+    /// Override colors with all values set in an IPC request.
+    pub fn update(&mut self, settings: &ThemeSettings) {
+        let colors = [
+            (&mut self.gesture_handle_default, settings.gesture_handle_color),
+            (&mut self.gesture_handle_locked, settings.gesture_handle_locked_color),
+            (&mut self.gesture_handle_blocked, settings.gesture_handle_blocked_color),
+            (&mut self.gesture_handle_background, settings.gesture_handle_background_color),
+            (&mut self.cursor, settings.cursor_color),
+            (&mut self.urgency_icon, settings.urgency_icon_color),
+            (&mut self.drop_target, settings.drop_target_color),
+            (&mut self.active_drop_target, settings.active_drop_target_color),
+            (&mut self.thumbnail_placeholder, settings.thumbnail_placeholder_color),
+            (&mut self.background, settings.background_color),
+        ];
+
+        for (color, new_color) in colors {
+            if let Some(Color(new_color)) = new_color {
+                *color = new_color;
+            }
+        }
+    }
+
+    /// Get the IPC representation of the theme.
+    pub fn ipc_theme(&self) -> ThemeSettings {
+        ThemeSettings {
+            gesture_handle_height: None,
+            gesture_handle_color: Some(Color(self.gesture_handle_default)),
+            gesture_handle_locked_color: Some(Color(self.gesture_handle_locked)),
+            gesture_handle_blocked_color: Some(Color(self.gesture_handle_blocked)),
+            gesture_handle_background_color: Some(Color(self.gesture_handle_background)),
+            cursor_color: Some(Color(self.cursor)),
+            urgency_icon_color: Some(Color(self.urgency_icon)),
+            drop_target_color: Some(Color(self.drop_target)),
+            active_drop_target_color: Some(Color(self.active_drop_target)),
+            thumbnail_placeholder_color: Some(Color(self.thumbnail_placeholder)),
+            background_color: Some(Color(self.background)),
+        }
+    }
+
+    /// Background color for the renderer's clear operations.
+    pub fn clear_color(&self) -> [f32; 4] {
+        self.background.map(|channel| channel as f32 / 255.)
+    }
+}
+
 /// Grahpics texture cache.
 #[derive(Debug)]
 pub struct Graphics {
     pub active_drop_target: RenderTexture,
+    pub thumbnail_placeholder: RenderTexture,
     pub urgency_icon: RenderTexture,
     pub drop_target: RenderTexture,
 
@@ -445,25 +533,38 @@ pub struct Graphics {
     gesture_handle_blocked: Option<RenderTexture>,
     gesture_handle_locked: Option<RenderTexture>,
     cursor: Option<RenderTexture>,
+    theme: Theme,
 }
 
 impl Graphics {
-    pub fn new() -> Self {
-        let active_drop_target = Texture::from_color(ACTIVE_DROP_TARGET_RGBA, false);
-        let drop_target = Texture::from_color(DROP_TARGET_RGBA, false);
-        let urgency_icon = Texture::from_color(URGENCY_ICON_RGBA, true);
+    pub fn new(theme: Theme) -> Self {
+        // Create textures for color fills, treating them as opaque without alpha.
+        let color_texture =
+            |color: [u8; 4]| RenderTexture::new(Texture::from_color(color, color[3] == u8::MAX));
 
         Self {
-            active_drop_target: RenderTexture::new(active_drop_target),
-            urgency_icon: RenderTexture::new(urgency_icon),
-            drop_target: RenderTexture::new(drop_target),
+            active_drop_target: color_texture(theme.active_drop_target),
+            thumbnail_placeholder: color_texture(theme.thumbnail_placeholder),
+            urgency_icon: color_texture(theme.urgency_icon),
+            drop_target: color_texture(theme.drop_target),
             gesture_handle_default: None,
             gesture_handle_blocked: None,
             gesture_handle_locked: None,
             cursor: None,
+            theme,
         }
     }
 
+    /// Get the active theme.
+    pub fn theme(&self) -> &Theme {
+        &self.theme
+    }
+
+    /// Change the theme, regenerating all textures.
+    pub fn set_theme(&mut self, theme: Theme) {
+        *self = Self::new(theme);
+    }
+
     /// Get texture for the gesture handle.
     pub fn gesture_handle(
         &mut self,
@@ -472,18 +573,19 @@ impl Graphics {
         ime_override: Option<bool>,
     ) -> RenderTexture {
         let (handle, color) = match ime_override {
-            None => (&mut self.gesture_handle_default, GESTURE_HANDLE_DEFAULT_RGB),
-            Some(true) => (&mut self.gesture_handle_locked, GESTURE_HANDLE_LOCKED_RGB),
-            Some(false) => (&mut self.gesture_handle_blocked, GESTURE_HANDLE_BLOCKED_RGB),
+            None => (&mut self.gesture_handle_default, self.theme.gesture_handle_default),
+            Some(true) => (&mut self.gesture_handle_locked, self.theme.gesture_handle_locked),
+            Some(false) => (&mut self.gesture_handle_blocked, self.theme.gesture_handle_blocked),
         };
 
         // Initialize texture or replace it after scale change.
         let scale = canvas.scale();
         let width = canvas.physical_size().w;
-        let height = (GESTURE_HANDLE_HEIGHT as f64 * scale).round() as i32;
+        let height = (canvas.gesture_handle_height() as f64 * scale).round() as i32;
         if handle.as_ref().is_none_or(|handle| handle.buffer_size() != (width, height).into()) {
-            // Initialize a black buffer with the correct size.
-            let mut buffer = vec![0; (height * width * 4) as usize];
+            // Initialize a buffer with the background color and correct size.
+            let background = self.theme.gesture_handle_background;
+            let mut buffer = background.repeat((height * width) as usize);
 
             // Calculate notch size.
             let notch_height = (height as f64 * GESTURE_NOTCH_PERCENTAGE) as i32;
@@ -498,12 +600,13 @@ impl Graphics {
                         && x < (width + notch_width) / 2
                     {
                         let offset = (y * width + x) as usize * 4;
-                        buffer[offset..offset + 3].copy_from_slice(&color);
+                        buffer[offset..offset + 4].copy_from_slice(&color);
                     }
                 }
             }
 
-            let texture = Texture::from_buffer(renderer, scale, &buffer, width, height, true);
+            let opaque = background[3] == u8::MAX && color[3] == u8::MAX;
+            let texture = Texture::from_buffer(renderer, scale, &buffer, width, height, opaque);
             *handle = Some(RenderTexture(Rc::new(texture)));
         }
 
@@ -524,7 +627,7 @@ impl Graphics {
                     let y_delta = (size as f64 / 2. - y as f64).floor();
                     if x_delta.powi(2) + y_delta.powi(2) <= (size as f64 / 2.).powi(2) {
                         let offset = (y * size + x) as usize * 4;
-                        buffer[offset..offset + 4].copy_from_slice(&CURSOR_RGBA);
+                        buffer[offset..offset + 4].copy_from_slice(&self.theme.cursor);
                     }
                 }
             }
@@ -11,6 +11,24 @@ pub struct Matrix3x3<T: Copy> {
     storage: Vec<T>,
 }
 
+impl<T: Copy> Matrix3x3<T> {
+    /// Get the first two rows of the matrix.
+    ///
+    /// This is the affine part of a 2D transformation matrix.
+    pub fn affine(&self) -> [T; 6] {
+        let s = &self.storage;
+        [s[0], s[1], s[2], s[3], s[4], s[5]]
+    }
+
+    /// Check if the last row is "0, 0, 1".
+    pub fn is_affine(&self) -> bool
+    where
+        T: PartialEq + From<u8>,
+    {
+        self.storage[6..] == [T::from(0), T::from(0), T::from(1)]
+    }
+}
+
 impl<T: Copy> TryFrom<Vec<T>> for Matrix3x3<T> {
     type Error = Box<dyn Error>;
 
@@ -257,4 +275,14 @@ mod test {
         let matrix = "1; 0; 0; 0; 1; 0; 0; 0; 1;";
         assert!(Matrix3x3::<i32>::from_str(matrix).is_err());
     }
+
+    #[test]
+    fn matrix_affine() {
+        let matrix = Matrix3x3::<f32>::from_str("0, 1, 0; -1, 0, 1; 0, 0, 1").unwrap();
+        assert_eq!(matrix.affine(), [0., 1., 0., -1., 0., 1.]);
+        assert!(matrix.is_affine());
+
+        let matrix = Matrix3x3::<f32>::from_str("1, 0, 0; 0, 1, 0; 0, 1, 1").unwrap();
+        assert!(!matrix.is_affine());
+    }
 }
@@ -0,0 +1,190 @@
+//! Built-in idle timeouts.
+
+use std::time::{Duration, Instant};
+
+use catacomb_ipc::{IdleAction, IdleTimeoutInfo};
+use smithay::input::keyboard::Keycode;
+use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
+use smithay::reexports::calloop::{LoopHandle, RegistrationToken};
+use tracing::error;
+
+use crate::catacomb::Catacomb;
+use crate::daemon;
+
+/// Idle timeout action scheduler.
+pub struct IdleManager {
+    /// Whether the display was turned off by an idle timeout.
+    pub display_off: bool,
+
+    /// Key which turned the display back on, its release is not forwarded.
+    pub wake_key: Option<Keycode>,
+
+    event_loop: LoopHandle<'static, Catacomb>,
+    timeouts: Vec<IdleTimeout>,
+    last_activity: Instant,
+    inhibited: bool,
+}
+
+impl IdleManager {
+    pub fn new(event_loop: LoopHandle<'static, Catacomb>) -> Self {
+        Self {
+            event_loop,
+            last_activity: Instant::now(),
+            display_off: Default::default(),
+            wake_key: Default::default(),
+            timeouts: Default::default(),
+            inhibited: Default::default(),
+        }
+    }
+
+    /// Add a new idle timeout.
+    pub fn add(&mut self, timeout: Duration, action: IdleAction) {
+        let mut timeout = IdleTimeout { timeout, action, timer: None };
+        if !self.inhibited {
+            timeout.start(&self.event_loop, self.timeouts.len());
+        }
+        self.timeouts.push(timeout);
+    }
+
+    /// Remove all idle timeouts.
+    pub fn clear(&mut self) {
+        for mut timeout in self.timeouts.drain(..) {
+            timeout.cancel(&self.event_loop);
+        }
+    }
+
+    /// Reset idle time after user activity.
+    ///
+    /// Active timers are rescheduled once they expire, so only timers which
+    /// have already elapsed need to be restarted here.
+    pub fn notify_activity(&mut self) {
+        self.last_activity = Instant::now();
+
+        if self.inhibited {
+            return;
+        }
+
+        for (index, timeout) in self.timeouts.iter_mut().enumerate() {
+            if timeout.timer.is_none() {
+                timeout.start(&self.event_loop, index);
+            }
+        }
+    }
+
+    /// Pause all timers while idle is inhibited.
+    pub fn set_inhibited(&mut self, inhibited: bool) {
+        if self.inhibited == inhibited {
+            return;
+        }
+        self.inhibited = inhibited;
+
+        if inhibited {
+            for timeout in &mut self.timeouts {
+                timeout.cancel(&self.event_loop);
+            }
+        } else {
+            self.notify_activity();
+        }
+    }
+
+    /// Get all idle timeouts.
+    pub fn ipc_timeouts(&self) -> Vec<IdleTimeoutInfo> {
+        self.timeouts
+            .iter()
+            .map(|timeout| IdleTimeoutInfo {
+                timeout: timeout.timeout.as_secs(),
+                action: timeout.action.clone(),
+            })
+            .collect()
+    }
+
+    /// Get the time at which a timeout will elapse.
+    fn deadline(&self, index: usize) -> Option<Instant> {
+        let timeout = self.timeouts.get(index)?;
+        Some(self.last_activity + timeout.timeout)
+    }
+
+    /// Mark a timeout as elapsed, returning its action.
+    fn elapse(&mut self, index: usize) -> Option<IdleAction> {
+        let timeout = self.timeouts.get_mut(index)?;
+        timeout.timer = None;
+        Some(timeout.action.clone())
+    }
+}
+
+/// Action performed after a period of inactivity.
+struct IdleTimeout {
+    timeout: Duration,
+    action: IdleAction,
+    timer: Option<RegistrationToken>,
+}
+
+impl IdleTimeout {
+    /// Start the timeout's timer.
+    fn start(&mut self, event_loop: &LoopHandle<'static, Catacomb>, index: usize) {
+        let timer = Timer::from_duration(self.timeout);
+        let token = event_loop
+            .insert_source(timer, move |_, _, catacomb| catacomb.on_idle_timeout(index))
+            .expect("insert idle timer");
+        self.timer = Some(token);
+    }
+
+    /// Stop the timeout's timer.
+    fn cancel(&mut self, event_loop: &LoopHandle<'static, Catacomb>) {
+        if let Some(timer) = self.timer.take() {
+            event_loop.remove(timer);
+        }
+    }
+}
+
+impl Catacomb {
+    /// Reset idle state after user activity.
+    pub fn notify_activity(&mut self) {
+        self.idle_notifier_state.notify_activity(&self.seat);
+        self.idle_manager.notify_activity();
+    }
+
+    /// Perform an elapsed idle timeout's action.
+    fn on_idle_timeout(&mut self, index: usize) -> TimeoutAction {
+        // Treat ongoing touch gestures as activity, since they only generate
+        // input events while the touch point is moving.
+        if self.touch_state.touching() {
+            self.notify_activity();
+        }
+
+        // Reschedule the timer if there was activity since it was started.
+        match self.idle_manager.deadline(index) {
+            Some(deadline) if deadline > Instant::now() => {
+                return TimeoutAction::ToInstant(deadline);
+            },
+            Some(_) => (),
+            None => return TimeoutAction::Drop,
+        }
+
+        let action = match self.idle_manager.elapse(index) {
+            Some(action) => action,
+            None => return TimeoutAction::Drop,
+        };
+
+        match action {
+            IdleAction::DpmsOff if self.display_on => {
+                self.set_display_status(false);
+                self.idle_manager.display_off = true;
+            },
+            IdleAction::DpmsOff => (),
+            IdleAction::Exec { program, arguments } => {
+                if let Err(err) = daemon::spawn(&program, &arguments) {
+                    error!("Failed to execute idle action ({program} {arguments:?}): {err}");
+                }
+            },
+            IdleAction::Lock { .. } if self.windows.locked() => (),
+            IdleAction::Lock { program, arguments } => {
+                if let Err(err) = daemon::spawn(&program, &arguments) {
+                    error!("Failed to start session locker ({program} {arguments:?}): {err}");
+                }
+            },
+        }
+
+        TimeoutAction::Drop
+    }
+}
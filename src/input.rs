@@ -2,50 +2,64 @@
 
 use std::time::{Duration, Instant};
 
-use catacomb_ipc::{GestureSector, KeyTrigger, Keysym, Modifiers};
+use catacomb_ipc::{
+    GestureSector, InputTimings, KeyStep, KeyTrigger, Keysym, Modifiers, TapGesture, TapLocation,
+    TouchGesture,
+};
 use smithay::backend::input::{
     AbsolutePositionEvent, ButtonState, Event, InputBackend, InputEvent, KeyState,
     KeyboardKeyEvent, MouseButton, PointerButtonEvent, TouchEvent as _, TouchSlot,
 };
 use smithay::input::keyboard::{
-    FilterResult, Keycode, KeysymHandle, ModifiersState, XkbConfig, keysyms,
+    FilterResult, Keycode, Keysym as XkbKeysym, KeysymHandle, ModifiersState, XkbConfig, keysyms,
 };
 use smithay::input::touch::{DownEvent, MotionEvent, UpEvent};
 use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
 use smithay::reexports::calloop::{LoopHandle, RegistrationToken};
-use smithay::utils::{Logical, Point, Rectangle, SERIAL_COUNTER};
+use smithay::utils::{Logical, Point, Rectangle, SERIAL_COUNTER, Size};
 use smithay::wayland::compositor;
 use tracing::error;
 
 use crate::catacomb::Catacomb;
-use crate::config::{GestureBinding, GestureBindingAction};
+use crate::config::{
+    EdgeSwipeBinding, GestureBinding, GestureBindingAction, KeyBinding, TapGestureBinding,
+    TouchGestureBinding,
+};
 use crate::daemon;
 use crate::drawing::CatacombSurfaceData;
 use crate::orientation::Orientation;
-use crate::output::{Canvas, GESTURE_HANDLE_HEIGHT};
+use crate::output::Canvas;
 use crate::windows::surface::{InputSurface, InputSurfaceKind};
 
-/// Time before a tap is considered a hold.
-pub const HOLD_DURATION: Duration = Duration::from_secs(1);
+/// Default time before a tap is considered a hold.
+const HOLD_DURATION: Duration = Duration::from_secs(1);
 
-/// Time before key repeat starts.
-pub const REPEAT_DELAY: Duration = Duration::from_millis(200);
+/// Default time before key repeat starts.
+const REPEAT_DELAY: Duration = Duration::from_millis(200);
 
-/// Key repeat interval after the first repetition.
-pub const REPEAT_RATE: Duration = Duration::from_millis(25);
+/// Default key repeat interval after the first repetition.
+const REPEAT_INTERVAL: Duration = Duration::from_millis(25);
 
-/// Maximum time between taps to be considered a double-tap.
+/// Default maximum time between taps to be considered a double-tap.
 const MAX_DOUBLE_TAP_DURATION: Duration = Duration::from_millis(300);
 
 /// Square of the maximum distance between taps to be considered a double-tap.
 const MAX_DOUBLE_TAP_DISTANCE: f64 = 2000.;
 
-/// Square of the maximum distance before touch input is considered a drag.
+/// Default square of the maximum distance before touch input is considered a
+/// drag.
 const MAX_TAP_DISTANCE: f64 = 400.;
 
-/// Friction for velocity computation.
+/// Default friction for velocity computation.
 const FRICTION: f64 = 0.1;
 
+/// Square of the minimum distance the center of all touch points must travel
+/// for multi-finger swipes.
+const MIN_MULTI_SWIPE_DISTANCE: f64 = 2500.;
+
+/// Minimum change in touch point spread to be considered a pinch.
+const MIN_PINCH_SCALE: f64 = 1.3;
+
 /// Touch slot for pointer emulation.
 ///
 /// The touch slot `None`, which is usually used for devices that do not support
@@ -53,15 +67,93 @@ const FRICTION: f64 = 0.1;
 /// around this we pick an arbitrary multitouch slot instead.
 const POINTER_TOUCH_SLOT: Option<u32> = Some(0);
 
+/// Runtime key repeat and touch gesture timings.
+#[derive(Copy, Clone, Debug)]
+pub struct Timings {
+    pub hold_duration: Duration,
+    pub repeat_delay: Duration,
+    pub repeat_interval: Duration,
+    pub max_double_tap_duration: Duration,
+    pub max_tap_distance: f64,
+    pub friction: f64,
+}
+
+impl Default for Timings {
+    fn default() -> Self {
+        Self {
+            hold_duration: HOLD_DURATION,
+            repeat_delay: REPEAT_DELAY,
+            repeat_interval: REPEAT_INTERVAL,
+            max_double_tap_duration: MAX_DOUBLE_TAP_DURATION,
+            max_tap_distance: MAX_TAP_DISTANCE,
+            friction: FRICTION,
+        }
+    }
+}
+
+impl Timings {
+    /// Override timings with all values set in an IPC request.
+    pub fn update(&mut self, timings: &InputTimings) {
+        if let Some(repeat_delay) = timings.repeat_delay {
+            self.repeat_delay = Duration::from_millis(repeat_delay);
+        }
+        if let Some(repeat_interval) = timings.repeat_interval {
+            self.repeat_interval = Duration::from_millis(repeat_interval);
+        }
+        if let Some(hold_duration) = timings.hold_duration {
+            self.hold_duration = Duration::from_millis(hold_duration);
+        }
+        if let Some(double_tap_duration) = timings.double_tap_duration {
+            self.max_double_tap_duration = Duration::from_millis(double_tap_duration);
+        }
+        if let Some(tap_distance) = timings.tap_distance {
+            self.max_tap_distance = tap_distance.powi(2);
+        }
+        if let Some(friction) = timings.friction {
+            self.friction = friction;
+        }
+    }
+
+    /// Get the IPC representation of the timings.
+    pub fn ipc_timings(&self) -> InputTimings {
+        InputTimings {
+            repeat_delay: Some(self.repeat_delay.as_millis() as u64),
+            repeat_interval: Some(self.repeat_interval.as_millis() as u64),
+            hold_duration: Some(self.hold_duration.as_millis() as u64),
+            double_tap_duration: Some(self.max_double_tap_duration.as_millis() as u64),
+            tap_distance: Some(self.max_tap_distance.sqrt()),
+            friction: Some(self.friction),
+        }
+    }
+
+    /// Keyboard repeat delay and rate for the seat's keyboard.
+    ///
+    /// Clients expect the repeat rate in repeats per second, rather than the
+    /// interval between repeats.
+    pub fn keyboard_repeat_info(&self) -> (i32, i32) {
+        let repeat_rate = (1000 / self.repeat_interval.as_millis().max(1)).max(1);
+        (self.repeat_delay.as_millis() as i32, repeat_rate as i32)
+    }
+}
+
 /// Touch input state.
 pub struct TouchState {
     pub user_gestures: Vec<GestureBinding>,
+    pub touch_gestures: Vec<TouchGestureBinding>,
+    pub edge_swipes: Vec<EdgeSwipeBinding>,
+    pub tap_gestures: Vec<TapGestureBinding>,
+    pub timings: Timings,
 
     last_tap: Option<(Instant, Point<f64, Logical>)>,
     pending_single_tap: Option<RegistrationToken>,
     event_loop: LoopHandle<'static, Catacomb>,
     velocity_timer: Option<RegistrationToken>,
     repeat_timer: Option<RegistrationToken>,
+    long_press_timer: Option<RegistrationToken>,
+    hold_timers: Vec<RegistrationToken>,
+    held_key: Option<HeldKey>,
+    key_presses: Vec<(Instant, KeyStep)>,
+    multi_touch: MultiTouch,
     input_surface: Option<InputSurface>,
     tap_surface: Option<InputSurface>,
     active_app_id: Option<String>,
@@ -81,16 +173,25 @@ impl TouchState {
             velocity_timer: Default::default(),
             input_surface: Default::default(),
             user_gestures: Default::default(),
+            touch_gestures: Default::default(),
+            edge_swipes: Default::default(),
+            tap_gestures: Default::default(),
             active_app_id: Default::default(),
             repeat_timer: Default::default(),
+            long_press_timer: Default::default(),
+            hold_timers: Default::default(),
             tap_surface: Default::default(),
             last_tap: Default::default(),
             position: Default::default(),
             velocity: Default::default(),
             is_drag: Default::default(),
+            key_presses: Default::default(),
+            multi_touch: Default::default(),
+            held_key: Default::default(),
             events: Default::default(),
             start: Default::default(),
             slot: Default::default(),
+            timings: Default::default(),
         }
     }
 
@@ -107,12 +208,13 @@ impl TouchState {
     where
         F: FnMut() + 'static,
     {
-        let timer = Timer::from_duration(REPEAT_DELAY);
+        let timer = Timer::from_duration(self.timings.repeat_delay);
+        let repeat_interval = self.timings.repeat_interval;
         let repeat_timer = self
             .event_loop
             .insert_source(timer, move |_, _, _| {
                 fun();
-                TimeoutAction::ToDuration(REPEAT_RATE)
+                TimeoutAction::ToDuration(repeat_interval)
             })
             .expect("insert key repeat timer");
         self.repeat_timer = Some(repeat_timer);
@@ -125,6 +227,56 @@ impl TouchState {
         }
     }
 
+    /// Record a key press for sequence bindings.
+    ///
+    /// Only the last `max_len` key presses are retained.
+    fn add_key_press(&mut self, step: KeyStep, max_len: usize) {
+        self.key_presses.push((Instant::now(), step));
+
+        let excess = self.key_presses.len().saturating_sub(max_len);
+        self.key_presses.drain(..excess);
+    }
+
+    /// Start a new key hold timer.
+    fn start_key_hold(
+        &mut self,
+        keysym: u32,
+        duration: Duration,
+        program: String,
+        arguments: Vec<String>,
+    ) {
+        self.held_key = Some(HeldKey { keysym, triggered: false });
+
+        let timer = Timer::from_duration(duration);
+        let hold_timer = self
+            .event_loop
+            .insert_source(timer, move |_, _, catacomb| {
+                if let Some(held_key) = &mut catacomb.touch_state.held_key {
+                    held_key.triggered = true;
+                }
+
+                let _ = daemon::spawn(&program, &arguments).inspect_err(|err| {
+                    error!("Failed keybinding command {program} {arguments:?}: {err}")
+                });
+
+                TimeoutAction::Drop
+            })
+            .expect("insert key hold timer");
+        self.hold_timers.push(hold_timer);
+    }
+
+    /// Stop all key hold timers.
+    ///
+    /// Returns the keysym of the held key if one of its hold bindings was
+    /// triggered.
+    fn cancel_key_hold(&mut self) -> Option<u32> {
+        for hold_timer in self.hold_timers.drain(..) {
+            self.event_loop.remove(hold_timer);
+        }
+
+        self.held_key.take().filter(|held_key| held_key.triggered).map(|held_key| held_key.keysym)
+    }
+
     /// Start a new touch session.
     fn start(&mut self, canvas: &Canvas, slot: TouchSlot, position: Point<f64, Logical>) {
         // Invalidate both sequences if more than one slot is active.
@@ -173,7 +325,9 @@ impl TouchState {
             }
 
             // Lock in direction once threshold was passed.
-            let gesture = HandleGesture::from_points(canvas, self.start.position, self.position);
+            let (start, end) = (self.start.position, self.position);
+            let gesture =
+                HandleGesture::from_points(canvas, start, end, self.timings.max_tap_distance);
             if let Some(gesture) = gesture {
                 self.start.handle_direction = Some(HandleDirection::from(&gesture));
                 return Some(gesture.into());
@@ -192,13 +346,26 @@ impl TouchState {
             if let Some(gesture) = gestures.next() {
                 return Some(TouchAction::UserGesture(gesture.action.clone()));
             }
+
+            // Find matching edge swipes.
+            let swipe_size = edge_swipe_size(canvas);
+            let duration = self.start.time.elapsed();
+            let swipe = self.edge_swipes.iter().find(|swipe| {
+                swipe.app_id.matches(app_id)
+                    && swipe.matches(swipe_size, start, self.position, duration)
+            });
+
+            if let Some(EdgeSwipeBinding { program, arguments, .. }) = swipe {
+                let action = GestureBindingAction::Cmd((program.clone(), arguments.clone()));
+                return Some(TouchAction::UserGesture(action));
+            }
         }
 
         // Convert to drag as soon as distance/time was exceeded once.
         let delta = self.start.position - self.position;
         if self.is_drag
-            || delta.x.powi(2) + delta.y.powi(2) > MAX_TAP_DISTANCE
-            || self.start.time.elapsed() >= HOLD_DURATION
+            || delta.x.powi(2) + delta.y.powi(2) > self.timings.max_tap_distance
+            || self.start.time.elapsed() >= self.timings.hold_duration
         {
             self.is_drag = true;
             return Some(TouchAction::Drag);
@@ -212,7 +379,7 @@ impl TouchState {
         // Report double-taps.
         if let Some(last_tap) = self.last_tap.take() {
             let last_tap_delta = last_tap.1 - self.position;
-            if last_tap.0.elapsed() <= MAX_DOUBLE_TAP_DURATION
+            if last_tap.0.elapsed() <= self.timings.max_double_tap_duration
                 && last_tap_delta.x.powi(2) + last_tap_delta.y.powi(2) <= MAX_DOUBLE_TAP_DISTANCE
             {
                 return Some(TouchAction::DoubleTap);
@@ -222,6 +389,104 @@ impl TouchState {
         Some(TouchAction::Tap)
     }
 
+    /// Start timer for long-press gestures.
+    fn start_long_press(&mut self, canvas: &Canvas, position: Point<f64, Logical>) {
+        let app_id = self.active_app_id.as_ref();
+        let action = match self.tap_gesture(canvas, app_id, TapGesture::LongPress, position) {
+            Some(TapGestureBinding { program, arguments, .. }) => {
+                GestureBindingAction::Cmd((program.clone(), arguments.clone()))
+            },
+            None => return,
+        };
+
+        let timer = Timer::from_duration(self.timings.hold_duration);
+        let long_press_timer = self
+            .event_loop
+            .insert_source(timer, move |_, _, catacomb| {
+                catacomb.on_long_press(action.clone());
+                TimeoutAction::Drop
+            })
+            .expect("insert long-press timer");
+        self.long_press_timer = Some(long_press_timer);
+    }
+
+    /// Cancel the long-press gesture timer.
+    fn cancel_long_press(&mut self) {
+        if let Some(long_press_timer) = self.long_press_timer.take() {
+            self.event_loop.remove(long_press_timer);
+        }
+    }
+
+    /// Reset all gesture state after the touch sequence was cancelled.
+    fn cancel(&mut self) {
+        self.cancel_long_press();
+        self.multi_touch.cancel();
+        self.input_surface = None;
+        self.tap_surface = None;
+        self.last_tap = None;
+        self.slot = None;
+    }
+
+    /// Check if a new touch could complete a double-tap gesture.
+    fn has_double_tap_gesture(
+        &self,
+        canvas: &Canvas,
+        app_id: Option<&String>,
+        position: Point<f64, Logical>,
+    ) -> bool {
+        let (last_time, last_position) = match self.last_tap {
+            Some(last_tap) => last_tap,
+            None => return false,
+        };
+
+        let delta = last_position - position;
+        last_time.elapsed() <= self.timings.max_double_tap_duration
+            && delta.x.powi(2) + delta.y.powi(2) <= MAX_DOUBLE_TAP_DISTANCE
+            && self.tap_gesture(canvas, app_id, TapGesture::DoubleTap, position).is_some()
+    }
+
+    /// Find the tap gesture binding at a touch position.
+    fn tap_gesture(
+        &self,
+        canvas: &Canvas,
+        app_id: Option<&String>,
+        gesture: TapGesture,
+        position: Point<f64, Logical>,
+    ) -> Option<&TapGestureBinding> {
+        let sector = GestureSector::from_point(canvas.size().to_f64(), position);
+        let on_handle = HandleGesture::is_start(canvas, position);
+
+        self.tap_gestures.iter().find(|binding| {
+            let location_matches = match binding.location {
+                TapLocation::Sector(binding_sector) => binding_sector == sector,
+                TapLocation::Handle => on_handle,
+            };
+            binding.gesture == gesture && location_matches && binding.app_id.matches(app_id)
+        })
+    }
+
+    /// Check if there are edge swipes starting at a touch position.
+    fn has_edge_swipe(
+        &self,
+        canvas: &Canvas,
+        app_id: Option<&String>,
+        position: Point<f64, Logical>,
+    ) -> bool {
+        let swipe_size = edge_swipe_size(canvas);
+        self.edge_swipes
+            .iter()
+            .any(|swipe| swipe.app_id.matches(app_id) && swipe.starts_at(swipe_size, position))
+    }
+
+    /// Check if there are multi-finger gestures for the active touch points.
+    fn has_touch_gesture(&self) -> bool {
+        let fingers = self.multi_touch.fingers();
+        let app_id = self.active_app_id.as_ref();
+        self.touch_gestures.iter().any(|gesture| {
+            usize::from(gesture.fingers) == fingers && gesture.app_id.matches(app_id)
+        })
+    }
+
     /// Find gestures matching an origin point.
     fn matching_gestures<'a>(
         &'a self,
@@ -306,11 +571,12 @@ impl HandleGesture {
         canvas: &Canvas,
         start: Point<f64, Logical>,
         end: Point<f64, Logical>,
+        max_tap_distance: f64,
     ) -> Option<Self> {
         // Ignore handle gestures until minimum length is exceeded.
         let delta = end - start;
         let squared_length = delta.x.powi(2) + delta.y.powi(2);
-        if squared_length < MAX_TAP_DISTANCE {
+        if squared_length < max_tap_distance {
             return None;
         }
 
@@ -326,7 +592,7 @@ impl HandleGesture {
     /// Check if a touch should start a new gesture.
     fn is_start(canvas: &Canvas, position: Point<f64, Logical>) -> bool {
         let canvas_size = canvas.size().to_f64();
-        let loc = (0., canvas_size.h - GESTURE_HANDLE_HEIGHT as f64).into();
+        let loc = (0., canvas_size.h - canvas.gesture_handle_height() as f64).into();
         Rectangle::new(loc, canvas_size).contains(position)
     }
 }
@@ -370,6 +636,145 @@ enum TouchEventType {
     Motion,
 }
 
+/// Touch point of a multi-finger gesture.
+#[derive(Copy, Clone, Debug)]
+struct TouchPoint {
+    start: Point<f64, Logical>,
+    position: Point<f64, Logical>,
+    slot: TouchSlot,
+    active: bool,
+}
+
+/// Multi-finger touch gesture state.
+#[derive(Debug)]
+struct MultiTouch {
+    points: Vec<TouchPoint>,
+    start_time: Instant,
+    intercepted: bool,
+}
+
+impl Default for MultiTouch {
+    fn default() -> Self {
+        Self {
+            start_time: Instant::now(),
+            intercepted: Default::default(),
+            points: Default::default(),
+        }
+    }
+}
+
+impl MultiTouch {
+    /// Add a new touch point.
+    fn down(&mut self, slot: TouchSlot, position: Point<f64, Logical>) {
+        // Start a new gesture once all fingers were lifted.
+        if !self.touching() {
+            *self = Self::default();
+        }
+
+        self.points.push(TouchPoint { slot, position, start: position, active: true });
+    }
+
+    /// Update a touch point's position.
+    fn motion(&mut self, slot: TouchSlot, position: Point<f64, Logical>) {
+        if let Some(point) = self.points.iter_mut().find(|point| point.active && point.slot == slot)
+        {
+            point.position = position;
+        }
+    }
+
+    /// Release a touch point.
+    fn up(&mut self, slot: TouchSlot) {
+        if let Some(point) = self.points.iter_mut().find(|point| point.active && point.slot == slot)
+        {
+            point.active = false;
+        }
+    }
+
+    /// Abort the gesture without dispatching it.
+    fn cancel(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Check if any touch point is still active.
+    fn touching(&self) -> bool {
+        self.points.iter().any(|point| point.active)
+    }
+
+    /// Number of fingers involved in the gesture.
+    fn fingers(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Get the gesture performed by all touch points.
+    fn gesture(&self, timings: &Timings) -> Option<TouchGesture> {
+        // Check if all fingers were lifted without moving.
+        let is_tap = self.points.iter().all(|point| {
+            let delta = point.position - point.start;
+            delta.x.powi(2) + delta.y.powi(2) <= timings.max_tap_distance
+        });
+        if is_tap {
+            return (self.start_time.elapsed() < timings.hold_duration)
+                .then_some(TouchGesture::Tap);
+        }
+
+        // Get average distance of the touch points from their center.
+        let start_center = self.center(|point| point.start);
+        let end_center = self.center(|point| point.position);
+        let start_spread = self.spread(start_center, |point| point.start);
+        let end_spread = self.spread(end_center, |point| point.position);
+
+        // Check for pinches.
+        if start_spread > 0. {
+            let scale = end_spread / start_spread;
+            if scale >= MIN_PINCH_SCALE {
+                return Some(TouchGesture::PinchOut);
+            } else if scale <= 1. / MIN_PINCH_SCALE {
+                return Some(TouchGesture::PinchIn);
+            }
+        }
+
+        // Check for swipes in the dominant direction.
+        let delta = end_center - start_center;
+        if delta.x.powi(2) + delta.y.powi(2) < MIN_MULTI_SWIPE_DISTANCE {
+            None
+        } else if delta.y.abs() >= delta.x.abs() {
+            Some(if delta.y < 0. { TouchGesture::SwipeUp } else { TouchGesture::SwipeDown })
+        } else {
+            Some(if delta.x < 0. { TouchGesture::SwipeLeft } else { TouchGesture::SwipeRight })
+        }
+    }
+
+    /// Get the center of all touch points.
+    fn center<F>(&self, position: F) -> Point<f64, Logical>
+    where
+        F: Fn(&TouchPoint) -> Point<f64, Logical>,
+    {
+        let count = self.points.len() as f64;
+        let sum = self.points.iter().fold(Point::default(), |sum, point| sum + position(point));
+        Point::from((sum.x / count, sum.y / count))
+    }
+
+    /// Get the average distance of all touch points from a center.
+    fn spread<F>(&self, center: Point<f64, Logical>, position: F) -> f64
+    where
+        F: Fn(&TouchPoint) -> Point<f64, Logical>,
+    {
+        let count = self.points.len() as f64;
+        let distances = self.points.iter().map(|point| {
+            let delta = position(point) - center;
+            (delta.x.powi(2) + delta.y.powi(2)).sqrt()
+        });
+        distances.sum::<f64>() / count
+    }
+}
+
+/// Key with pending or triggered hold bindings.
+#[derive(Copy, Clone, Debug)]
+struct HeldKey {
+    keysym: u32,
+    triggered: bool,
+}
+
 impl Catacomb {
     /// Process device orientation changes.
     pub fn handle_orientation(&mut self, orientation: Orientation) {
@@ -399,6 +804,17 @@ impl Catacomb {
         }
     }
 
+    /// Change key repeat and touch gesture timings.
+    pub fn set_timings(&mut self, timings: Timings) {
+        self.touch_state.timings = timings;
+
+        // Update repeat info advertised to clients.
+        if let Some(keyboard) = self.seat.get_keyboard() {
+            let (repeat_delay, repeat_rate) = timings.keyboard_repeat_info();
+            keyboard.change_repeat_info(repeat_rate, repeat_delay);
+        }
+    }
+
     /// Process new input events.
     pub fn handle_input<I: InputBackend>(&mut self, event: InputEvent<I>) {
         // Ignore events captured while the display is off.
@@ -407,9 +823,16 @@ impl Catacomb {
         }
 
         // Reset idle sleep timer.
-        self.idle_notifier_state.notify_activity(&self.seat);
+        self.notify_activity();
 
         match event {
+            // Ignore release of the key which woke up the display.
+            InputEvent::Keyboard { event, .. }
+                if event.state() == KeyState::Released
+                    && self.idle_manager.wake_key == Some(event.key_code()) =>
+            {
+                self.idle_manager.wake_key = None;
+            },
             InputEvent::Keyboard { event, .. } => {
                 let time = Event::time(&event) as u32;
                 let code = event.key_code();
@@ -468,6 +891,13 @@ impl Catacomb {
             // Handle gesture touch cancel for nested compositors.
             InputEvent::TouchCancel { event } => {
                 self.touch_state.events.retain(|touch_event| touch_event.slot != event.slot());
+
+                // Notify client.
+                if self.touch_state.input_surface.is_some() {
+                    self.seat.get_touch().unwrap().cancel(self);
+                }
+
+                self.touch_state.cancel();
             },
             _ => (),
         }
@@ -481,6 +911,13 @@ impl Catacomb {
     /// This will return `true` if the event should be suppressed.
     fn handle_display_off_input<I: InputBackend>(&mut self, event: &InputEvent<I>) -> bool {
         match event {
+            // Wake up from idle timeouts on key press, without processing the key.
+            InputEvent::Keyboard { event, .. }
+                if self.idle_manager.display_off && event.state() == KeyState::Pressed =>
+            {
+                self.idle_manager.wake_key = Some(event.key_code());
+                self.set_display_status(true);
+            },
             // Allow keyboard input while screen is off.
             InputEvent::Keyboard { .. } => return false,
             InputEvent::TouchDown { event } => {
@@ -502,6 +939,7 @@ impl Catacomb {
                         TouchEventType::Down => {
                             let canvas = self.windows.canvas();
                             self.touch_state.start(canvas, event.slot, event.position);
+                            self.touch_state.multi_touch = MultiTouch::default();
                         },
                         TouchEventType::Up if self.touch_state.slot.take() == Some(event.slot) => {
                             match self.touch_state.action(self.windows.canvas()) {
@@ -529,9 +967,27 @@ impl Catacomb {
     fn on_touch_down(&mut self, event: TouchEvent) {
         let TouchEvent { slot, position, .. } = event;
 
+        // Long-press gestures are only valid for a single touch point.
+        self.touch_state.cancel_long_press();
+
         // Initialize the touch state.
         self.touch_state.start(self.windows.canvas(), slot, position);
 
+        // Intercept touch input once a multi-finger gesture is bound.
+        self.touch_state.multi_touch.down(slot, position);
+        if self.touch_state.multi_touch.intercepted {
+            return;
+        } else if self.touch_state.multi_touch.fingers() > 1 && self.touch_state.has_touch_gesture()
+        {
+            self.touch_state.multi_touch.intercepted = true;
+            self.touch_state.input_surface = None;
+
+            // Notify client.
+            self.seat.get_touch().unwrap().cancel(self);
+
+            return;
+        }
+
         // Find surface at touch position.
         let surface = self.windows.surface_at(event.position);
 
@@ -539,22 +995,30 @@ impl Catacomb {
         self.touch_state.input_surface = None;
         self.touch_state.active_app_id = None;
         self.touch_state.tap_surface = None;
+        let mut inhibits_shortcuts = false;
         if let Some(mut input_surface) = surface {
             // Get surface's App ID.
             let app_id = input_surface.toplevel.as_mut().and_then(InputSurfaceKind::take_app_id);
 
             // Check if surface captures shortcuts.
-            let inhibits_shortcuts = compositor::with_states(&input_surface.surface, |states| {
+            inhibits_shortcuts = compositor::with_states(&input_surface.surface, |states| {
                 let data = states.data_map.get::<CatacombSurfaceData>();
                 data.is_some_and(|data| data.inhibits_shortcuts)
             });
 
             // Check if there's a gesture for this touch event.
             let has_gesture = || {
+                let canvas = self.windows.canvas();
                 self.touch_state
-                    .matching_gestures(self.windows.canvas(), app_id.as_ref(), event.position, None)
+                    .matching_gestures(canvas, app_id.as_ref(), event.position, None)
                     .next()
                     .is_some()
+                    || self.touch_state.has_edge_swipe(canvas, app_id.as_ref(), event.position)
+                    || self.touch_state.has_double_tap_gesture(
+                        canvas,
+                        app_id.as_ref(),
+                        event.position,
+                    )
             };
 
             // Check if a gesture is triggered by this touch event.
@@ -573,12 +1037,32 @@ impl Catacomb {
 
         // Only send touch start if there's no handle gesture in progress.
         if !self.touch_state.start.is_handle_gesture {
-            self.windows.on_touch_start(position);
+            self.windows.on_touch_start(position, self.touch_state.timings.hold_duration);
+        }
+
+        // Stage long-press gestures.
+        if self.touch_state.slot == Some(slot) && !inhibits_shortcuts {
+            self.touch_state.start_long_press(self.windows.canvas(), position);
         }
     }
 
     /// Handle touch input release.
     fn on_touch_up(&mut self, event: TouchEvent) {
+        self.touch_state.cancel_long_press();
+
+        // Dispatch multi-finger gestures once all fingers were lifted.
+        self.touch_state.multi_touch.up(event.slot);
+        if self.touch_state.multi_touch.intercepted {
+            if self.touch_state.slot == Some(event.slot) {
+                self.touch_state.slot = None;
+            }
+
+            if !self.touch_state.multi_touch.touching() {
+                self.on_touch_gesture();
+            }
+
+            return;
+        }
         // Notify client.
         if self.touch_state.input_surface.is_some() {
             let serial = SERIAL_COUNTER.next_serial();
@@ -600,7 +1084,7 @@ impl Catacomb {
                 self.replay_ignored_tap(event);
 
                 // Stage single-tap to trigger on double-tap timeout.
-                let timer = Timer::from_duration(MAX_DOUBLE_TAP_DURATION);
+                let timer = Timer::from_duration(self.touch_state.timings.max_double_tap_duration);
                 let pending_single_tap =
                     self.event_loop.insert_source(timer, Self::on_single_tap).unwrap();
                 self.touch_state.pending_single_tap = Some(pending_single_tap);
@@ -612,6 +1096,19 @@ impl Catacomb {
                     self.event_loop.remove(pending_single_tap);
                 }
 
+                // Dispatch user double-tap gestures.
+                let canvas = self.windows.canvas();
+                let app_id = self.touch_state.active_app_id.as_ref();
+                let position = self.touch_state.position;
+                let gesture = TapGesture::DoubleTap;
+                if let Some(TapGestureBinding { program, arguments, .. }) =
+                    self.touch_state.tap_gesture(canvas, app_id, gesture, position)
+                {
+                    let action = GestureBindingAction::Cmd((program.clone(), arguments.clone()));
+                    self.on_user_gesture(action);
+                    return;
+                }
+
                 self.replay_ignored_tap(event);
 
                 self.on_double_tap();
@@ -632,6 +1129,20 @@ impl Catacomb {
         self.touch_state.velocity = event.position - self.touch_state.position;
         self.touch_state.position = event.position;
 
+        // Cancel long-press once the touch point starts moving.
+        if self.touch_state.slot == Some(event.slot) {
+            let delta = event.position - self.touch_state.start.position;
+            if delta.x.powi(2) + delta.y.powi(2) > self.touch_state.timings.max_tap_distance {
+                self.touch_state.cancel_long_press();
+            }
+        }
+
+        // Track fingers of multi-finger gestures.
+        self.touch_state.multi_touch.motion(event.slot, event.position);
+        if self.touch_state.multi_touch.intercepted {
+            return;
+        }
+
         // Handle client input.
         if let Some(input_surface) = &self.touch_state.input_surface {
             // Convert position to pre-window scaling.
@@ -725,6 +1236,47 @@ impl Catacomb {
         self.seat.get_touch().unwrap().cancel(self);
     }
 
+    /// Dispatch long-press gestures.
+    fn on_long_press(&mut self, action: GestureBindingAction) {
+        self.touch_state.long_press_timer = None;
+
+        // Ignore the remaining touch sequence.
+        self.touch_state.slot = None;
+        self.touch_state.tap_surface = None;
+
+        // Notify client.
+        if self.touch_state.input_surface.take().is_some() {
+            self.seat.get_touch().unwrap().cancel(self);
+        }
+
+        self.on_user_gesture(action);
+    }
+
+    /// Dispatch multi-finger touch gestures.
+    fn on_touch_gesture(&mut self) {
+        let multi_touch = &self.touch_state.multi_touch;
+        let gesture = match multi_touch.gesture(&self.touch_state.timings) {
+            Some(gesture) => gesture,
+            None => return,
+        };
+
+        // Find matching gesture binding.
+        let fingers = multi_touch.fingers();
+        let app_id = self.touch_state.active_app_id.as_ref();
+        let binding = self.touch_state.touch_gestures.iter().find(|binding| {
+            usize::from(binding.fingers) == fingers
+                && binding.gesture == gesture
+                && binding.app_id.matches(app_id)
+        });
+
+        // Execute subcommand.
+        if let Some(TouchGestureBinding { program, arguments, .. }) = binding {
+            if let Err(err) = daemon::spawn(program, arguments) {
+                error!("Failed touch gesture command {program} {arguments:?}: {err}");
+            }
+        }
+    }
+
     /// Dispatch user gestures.
     fn on_user_gesture(&mut self, action: GestureBindingAction) {
         match action {
@@ -809,13 +1361,14 @@ impl Catacomb {
         // The animations are designed for 60FPS, but should still behave properly for
         // other refresh rates.
         let frame_interval = self.windows.canvas().frame_interval();
+        let friction = self.touch_state.timings.friction;
         let velocity = &mut self.touch_state.velocity;
         let position = &mut self.touch_state.position;
         let animation_speed = frame_interval.as_millis() as f64 / 16.;
         velocity.x -= velocity.x.signum()
-            * (velocity.x.abs() * FRICTION * animation_speed + 1.).min(velocity.x.abs());
+            * (velocity.x.abs() * friction * animation_speed + 1.).min(velocity.x.abs());
         velocity.y -= velocity.y.signum()
-            * (velocity.y.abs() * FRICTION * animation_speed + 1.).min(velocity.y.abs());
+            * (velocity.y.abs() * friction * animation_speed + 1.).min(velocity.y.abs());
         position.x += velocity.x * animation_speed;
         position.y += velocity.y * animation_speed;
 
@@ -971,6 +1524,15 @@ impl Catacomb {
         // Cancel active key repeat timers.
         catacomb.touch_state.cancel_key_repeat();
 
+        // Cancel hold timers on new key press or release of the held key.
+        let pressed = state == KeyState::Pressed;
+        let held_keysym = catacomb.touch_state.held_key.map(|held_key| held_key.keysym);
+        let hold_triggered = if pressed || held_keysym == Some(raw_keysym) {
+            catacomb.touch_state.cancel_key_hold() == Some(raw_keysym)
+        } else {
+            false
+        };
+
         // Check if focused surface inhibits shortcuts.
         let inhibits_shortcuts = catacomb.last_focus().is_some_and(|surface| {
             compositor::with_states(surface, |states| {
@@ -983,19 +1545,57 @@ impl Catacomb {
         }
 
         let mods = mods.into();
+        let step = KeyStep { mods, key: Keysym::Xkb(raw_keysym) };
+
+        // Track key presses for sequence bindings.
+        //
+        // Modifiers are ignored, since they're already part of the modified key's step.
+        if pressed && !XkbKeysym::new(raw_keysym).is_modifier_key() {
+            let bindings = catacomb.key_bindings.iter();
+            let max_len = bindings.map(|binding| binding.sequence.len() + 1).max().unwrap_or(1);
+            catacomb.touch_state.add_key_press(step, max_len);
+        }
 
         // Get currently focused app.
         let active_app = catacomb.windows.focus().and_then(|(_, app_id)| app_id);
 
+        // Find bindings completed by this key.
+        let key_presses = catacomb.touch_state.key_presses.clone();
+        let completed = |key_binding: &KeyBinding| {
+            key_binding.last_step() == step
+                && key_binding.app_id.matches(active_app.as_ref())
+                && (key_binding.sequence.is_empty() || key_binding.sequence_completed(&key_presses))
+        };
+
+        // Only consider the longest completed sequence, to avoid triggering bindings of
+        // its last key.
+        let bindings = catacomb.key_bindings.iter().filter(|binding| completed(binding));
+        let sequence_len = bindings.map(|binding| binding.sequence.len()).max();
+
         // Execute all matching keybindings.
         let mut filter_result = FilterResult::Forward;
-        let pressed = state == KeyState::Pressed;
         for key_binding in &catacomb.key_bindings {
-            if key_binding.key == Keysym::Xkb(raw_keysym)
-                && key_binding.mods == mods
-                && key_binding.app_id.matches(active_app.as_ref())
+            if completed(key_binding)
+                && Some(key_binding.sequence.len()) == sequence_len
                 && (key_binding.trigger != KeyTrigger::Release) == pressed
             {
+                // Prevent key propagation.
+                filter_result = InputAction::None.into();
+
+                // Stage timer for bindings triggered on hold.
+                if let KeyTrigger::Hold(duration) = key_binding.trigger {
+                    let program = key_binding.program.clone();
+                    let arguments = key_binding.arguments.clone();
+                    let duration = Duration::from_millis(duration);
+                    catacomb.touch_state.start_key_hold(raw_keysym, duration, program, arguments);
+                    continue;
+                }
+
+                // Skip release bindings if the key was held.
+                if hold_triggered {
+                    continue;
+                }
+
                 // Execute subcommand.
                 let program = &key_binding.program;
                 let arguments = &key_binding.arguments;
@@ -1013,9 +1613,6 @@ impl Catacomb {
                         });
                     });
                 }
-
-                // Prevent key propagation.
-                filter_result = InputAction::None.into();
             }
         }
 
@@ -1084,3 +1681,80 @@ impl From<InputAction> for FilterResult<InputAction> {
         FilterResult::Intercept(action)
     }
 }
+
+/// Get the area available for edge swipes, excluding the gesture handle.
+fn edge_swipe_size(canvas: &Canvas) -> Size<f64, Logical> {
+    let mut size = canvas.size().to_f64();
+    size.h -= canvas.gesture_handle_height() as f64;
+    size
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn multi_touch_gesture() {
+        let timings = Timings::default();
+
+        let tap = [((0., 0.), (0., 0.)), ((100., 0.), (105., 0.))];
+        assert_eq!(gesture(&tap, &timings), Some(TouchGesture::Tap));
+
+        let swipe_up = [((0., 200.), (0., 100.)), ((100., 200.), (100., 100.))];
+        assert_eq!(gesture(&swipe_up, &timings), Some(TouchGesture::SwipeUp));
+
+        let swipe_right = [((0., 0.), (100., 10.)), ((0., 100.), (100., 110.))];
+        assert_eq!(gesture(&swipe_right, &timings), Some(TouchGesture::SwipeRight));
+
+        let pinch_out = [((100., 100.), (50., 100.)), ((200., 100.), (250., 100.))];
+        assert_eq!(gesture(&pinch_out, &timings), Some(TouchGesture::PinchOut));
+
+        let pinch_in = [((50., 100.), (100., 100.)), ((250., 100.), (200., 100.))];
+        assert_eq!(gesture(&pinch_in, &timings), Some(TouchGesture::PinchIn));
+
+        let too_short = [((0., 0.), (0., 30.)), ((100., 0.), (100., 30.))];
+        assert_eq!(gesture(&too_short, &timings), None);
+    }
+
+    #[test]
+    fn multi_touch_cancel() {
+        let mut multi_touch = MultiTouch::default();
+        multi_touch.down(TouchSlot::from(Some(0)), Point::from((0., 0.)));
+        multi_touch.down(TouchSlot::from(Some(1)), Point::from((100., 0.)));
+        multi_touch.intercepted = true;
+
+        multi_touch.cancel();
+        assert!(!multi_touch.touching());
+
+        // New touch sequence must not be intercepted.
+        multi_touch.down(TouchSlot::from(Some(0)), Point::from((0., 0.)));
+        assert!(!multi_touch.intercepted);
+        assert_eq!(multi_touch.fingers(), 1);
+    }
+
+    #[test]
+    fn keyboard_repeat_rate() {
+        let mut timings = Timings::default();
+        assert_eq!(timings.keyboard_repeat_info(), (200, 40));
+
+        timings.repeat_interval = Duration::from_millis(100);
+        assert_eq!(timings.keyboard_repeat_info(), (200, 10));
+
+        timings.repeat_interval = Duration::from_millis(5000);
+        assert_eq!(timings.keyboard_repeat_info(), (200, 1));
+    }
+
+    // Get the gesture for touch points moving from their start to end position.
+    fn gesture(points: &[((f64, f64), (f64, f64))], timings: &Timings) -> Option<TouchGesture> {
+        let mut multi_touch = MultiTouch::default();
+        for (i, (start, _)) in points.iter().enumerate() {
+            multi_touch.down(TouchSlot::from(Some(i as u32)), Point::from(*start));
+        }
+        for (i, (_, end)) in points.iter().enumerate() {
+            let slot = TouchSlot::from(Some(i as u32));
+            multi_touch.motion(slot, Point::from(*end));
+            multi_touch.up(slot);
+        }
+        multi_touch.gesture(timings)
+    }
+}
@@ -1,19 +1,38 @@
 //! IPC socket server.
 
 use std::error::Error;
-use std::fs;
-use std::io::{BufRead, BufReader, Write};
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
 use std::os::unix::net::{UnixListener, UnixStream};
 use std::path::PathBuf;
+use std::time::Duration;
+use std::{fs, mem};
 
-use catacomb_ipc::{AppIdMatcher, CliToggle, IpcMessage, Keysym, WindowScale};
+use catacomb_ipc::{
+    AppIdMatcher, CliToggle, ErrorKind, EventKind, InputSettings, InputTimings, IpcError, IpcEvent,
+    IpcMessage, Keysym, LayoutAction, Orientation, ThemeSettings, WindowAction, WindowInfo,
+    WindowRule, WindowRuleInfo, WindowScale, WindowScaleInfo,
+};
 use smithay::input::keyboard::XkbConfig;
-use smithay::reexports::calloop::LoopHandle;
+use smithay::reexports::calloop::generic::Generic;
+use smithay::reexports::calloop::{Interest, LoopHandle, Mode, PostAction};
+use smithay::utils::Rectangle;
 use tracing::{error, warn};
 
 use crate::catacomb::Catacomb;
-use crate::config::{GestureBinding, GestureBindingAction, KeyBinding};
+use crate::config::{
+    DEFAULT_EDGE_SWIPE_DISTANCE, DEFAULT_SEQUENCE_TIMEOUT, EdgeSwipeBinding, GestureBinding,
+    GestureBindingAction, KeyBinding, TapGestureBinding, TouchGestureBinding,
+};
+use crate::recording::RecordingTarget;
 use crate::socket::SocketSource;
+use crate::udev;
+
+/// Maximum time spent writing a single message to an IPC client.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Maximum number of bytes read from an IPC client at once.
+const READ_CHUNK_SIZE: usize = 4096;
 
 /// Create an IPC socket.
 pub fn spawn_ipc_socket(
@@ -32,85 +51,152 @@ pub fn spawn_ipc_socket(
     let socket = SocketSource::new(listener)?;
 
     // Add source to calloop loop.
-    let mut message_buffer = String::new();
-    event_loop.insert_source(socket, move |stream, _, catacomb| {
-        handle_message(&mut message_buffer, stream, catacomb);
-    })?;
+    event_loop.insert_source(socket, |stream, _, catacomb| add_client(stream, catacomb))?;
 
     Ok(socket_path)
 }
 
-/// Handle IPC socket messages.
-fn handle_message(buffer: &mut String, mut stream: UnixStream, catacomb: &mut Catacomb) {
-    buffer.clear();
+/// Register a new IPC client connection.
+fn add_client(stream: UnixStream, catacomb: &mut Catacomb) {
+    // Ensure unresponsive clients cannot block the compositor.
+    if let Err(err) = stream.set_write_timeout(Some(WRITE_TIMEOUT)) {
+        error!("Could not accept IPC client: {err}");
+        return;
+    }
+
+    let mut buffer = Vec::new();
+    let source = Generic::new(stream, Interest::READ, Mode::Level);
+    let result = catacomb.event_loop.insert_source(source, move |_, stream, catacomb| {
+        Ok(handle_client(&mut buffer, stream, catacomb))
+    });
+
+    if let Err(err) = result {
+        error!("Could not register IPC client: {err}");
+    }
+}
+
+/// Handle IPC client socket readiness.
+///
+/// Every newline-terminated message is handled separately, allowing clients
+/// to send multiple requests over a single connection.
+fn handle_client(buffer: &mut Vec<u8>, stream: &UnixStream, catacomb: &mut Catacomb) -> PostAction {
+    // Read available data, this will not block since the socket is readable.
+    let mut chunk = [0; READ_CHUNK_SIZE];
+    let mut reader = stream;
+    let read = match reader.read(&mut chunk) {
+        Ok(read) => read,
+        Err(err) if err.kind() == io::ErrorKind::Interrupted => return PostAction::Continue,
+        // Subscriber streams are non-blocking.
+        Err(err) if err.kind() == io::ErrorKind::WouldBlock => return PostAction::Continue,
+        Err(err) => {
+            warn!("Dropping IPC client: {err}");
+            return PostAction::Remove;
+        },
+    };
+    buffer.extend_from_slice(&chunk[..read]);
+
+    // Handle all complete messages.
+    while let Some(index) = buffer.iter().position(|byte| *byte == b'\n') {
+        let message: Vec<u8> = buffer.drain(..=index).collect();
+        handle_message(&message, stream, catacomb);
+    }
 
-    // Read new content to buffer.
-    let mut reader = BufReader::new(&stream);
-    if let Ok(0) | Err(_) = reader.read_line(buffer) {
+    // Handle unterminated message once the client is done writing.
+    if read == 0 {
+        handle_message(&mem::take(buffer), stream, catacomb);
+        return PostAction::Remove;
+    }
+
+    PostAction::Continue
+}
+
+/// Handle IPC socket messages.
+fn handle_message(message: &[u8], stream: &UnixStream, catacomb: &mut Catacomb) {
+    // Ignore empty lines.
+    if message.trim_ascii().is_empty() {
         return;
     }
 
     // Read pending events on socket.
-    let message: IpcMessage = match serde_json::from_str(buffer) {
+    let message: IpcMessage = match serde_json::from_slice(message) {
         Ok(message) => message,
-        Err(_) => return,
+        Err(err) => {
+            let protocol = catacomb_ipc::PROTOCOL_VERSION;
+            let message = format!("invalid message for IPC protocol version {protocol}: {err}");
+            let error = IpcError::new(ErrorKind::InvalidMessage, message);
+            warn!("ignoring invalid ipc message: {error}");
+            send_reply(stream, &error.into());
+            return;
+        },
+    };
+
+    // Keep the connection open for event subscribers.
+    if let IpcMessage::Subscribe { events } = message {
+        match stream.try_clone() {
+            Ok(stream) => {
+                send_reply(&stream, &IpcMessage::Ok);
+                if let Err(err) = catacomb.ipc_subscribers.add(stream, events) {
+                    error!("Could not add IPC subscriber: {err}");
+                }
+            },
+            Err(err) => error!("Could not add IPC subscriber: {err}"),
+        }
+        return;
+    }
+
+    let reply = match handle_request(message, catacomb) {
+        Ok(reply) => reply.unwrap_or(IpcMessage::Ok),
+        Err(error) => {
+            warn!("ignoring invalid ipc message: {error}");
+            error.into()
+        },
     };
+    send_reply(stream, &reply);
+}
 
-    // Handle IPC events.
+/// Handle a single IPC request.
+///
+/// Returns the reply for requests which query compositor state.
+pub fn handle_request(
+    message: IpcMessage,
+    catacomb: &mut Catacomb,
+) -> Result<Option<IpcMessage>, IpcError> {
     match message {
         IpcMessage::Orientation { unlock: true, .. } => catacomb.unlock_orientation(),
         IpcMessage::Orientation { lock: orientation, .. } => catacomb.lock_orientation(orientation),
         IpcMessage::Scale { scale, app_id: Some(app_id) } => {
-            let app_id = match AppIdMatcher::try_from(app_id) {
-                Ok(app_id) => app_id,
-                Err(err) => {
-                    warn!("ignoring invalid ipc message: scale has invalid App ID regex: {err}");
-                    return;
-                },
-            };
-
+            let app_id = app_id_matcher(app_id)?;
             catacomb.windows.add_window_scale(app_id, scale);
         },
         IpcMessage::Scale { scale, app_id: None } => {
             let scale = match scale {
                 WindowScale::Fixed(scale) => scale,
                 scale => {
-                    warn!("ignoring invalid ipc message: expected fixed scale, got {scale:?}");
-                    return;
+                    let message = format!("global scale must be fixed, got \"{scale}\"");
+                    return Err(IpcError::new(ErrorKind::InvalidScale, message));
                 },
             };
 
             catacomb.windows.set_scale(scale);
             catacomb.unstall();
         },
+        IpcMessage::Unscale { app_id } => catacomb.windows.remove_window_scales(app_id.as_deref()),
         IpcMessage::BindGesture { app_id, start, end, program, arguments } => {
-            let app_id = match AppIdMatcher::try_from(app_id) {
-                Ok(app_id) => app_id,
-                Err(err) => {
-                    warn!("ignoring invalid ipc message: binding has invalid App ID regex: {err}");
-                    return;
-                },
-            };
+            let app_id = app_id_matcher(app_id)?;
 
             let action = GestureBindingAction::Cmd((program, arguments));
             let gesture = GestureBinding { app_id, start, end, action };
             catacomb.touch_state.user_gestures.push(gesture);
         },
         IpcMessage::BindGestureKey { app_id, start, end, mods, key } => {
-            let app_id = match AppIdMatcher::try_from(app_id) {
-                Ok(app_id) => app_id,
-                Err(err) => {
-                    warn!("ignoring invalid ipc message: binding has invalid App ID regex: {err}");
-                    return;
-                },
-            };
+            let app_id = app_id_matcher(app_id)?;
 
             // Ignore custom keysyms like virtual keyboard enable/disable.
             let key = match key {
                 Keysym::Xkb(key) => key,
                 _ => {
-                    warn!("ignoring invalid ipc message: gestures must be bound to real keysyms");
-                    return;
+                    let message = "gestures must be bound to real keysyms";
+                    return Err(IpcError::new(ErrorKind::InvalidKeysym, message));
                 },
             };
 
@@ -123,17 +209,83 @@ fn handle_message(buffer: &mut String, mut stream: UnixStream, catacomb: &mut Ca
                 gesture.app_id.base() != app_id || gesture.start != start || gesture.end != end
             });
         },
-        IpcMessage::BindKey { app_id, mods, trigger, key, program, arguments } => {
-            let app_id = match AppIdMatcher::try_from(app_id) {
-                Ok(app_id) => app_id,
-                Err(err) => {
-                    warn!("ignoring invalid ipc message: binding has invalid App ID regex: {err}");
-                    return;
-                },
+        IpcMessage::BindTouchGesture { fingers: 0..=1, .. } => {
+            let message = "touch gestures require at least two fingers";
+            return Err(IpcError::new(ErrorKind::InvalidMessage, message));
+        },
+        IpcMessage::BindTouchGesture { app_id, fingers, gesture, program, arguments } => {
+            let app_id = app_id_matcher(app_id)?;
+
+            let binding = TouchGestureBinding { app_id, fingers, gesture, program, arguments };
+            catacomb.touch_state.touch_gestures.push(binding);
+        },
+        IpcMessage::UnbindTouchGesture { app_id, fingers, gesture } => {
+            catacomb.touch_state.touch_gestures.retain(|binding| {
+                binding.app_id.base() != app_id
+                    || binding.fingers != fingers
+                    || binding.gesture != gesture
+            });
+        },
+        IpcMessage::BindEdgeSwipe {
+            app_id,
+            edge,
+            direction,
+            min_distance,
+            min_velocity,
+            program,
+            arguments,
+        } => {
+            let app_id = app_id_matcher(app_id)?;
+
+            let binding = EdgeSwipeBinding {
+                min_velocity,
+                arguments,
+                program,
+                app_id,
+                edge,
+                min_distance: min_distance.unwrap_or(DEFAULT_EDGE_SWIPE_DISTANCE),
+                direction: direction.unwrap_or(edge.inward()),
             };
+            catacomb.touch_state.edge_swipes.push(binding);
+        },
+        IpcMessage::UnbindEdgeSwipe { app_id, edge, direction } => {
+            catacomb.touch_state.edge_swipes.retain(|binding| {
+                binding.app_id.base() != app_id
+                    || binding.edge != edge
+                    || direction.is_some_and(|direction| binding.direction != direction)
+            });
+        },
+        IpcMessage::BindTapGesture { app_id, gesture, location, program, arguments } => {
+            let app_id = app_id_matcher(app_id)?;
 
+            let binding = TapGestureBinding { app_id, gesture, location, program, arguments };
+            catacomb.touch_state.tap_gestures.push(binding);
+        },
+        IpcMessage::UnbindTapGesture { app_id, gesture, location } => {
+            catacomb.touch_state.tap_gestures.retain(|binding| {
+                binding.app_id.base() != app_id
+                    || binding.gesture != gesture
+                    || binding.location != location
+            });
+        },
+        IpcMessage::BindKey {
+            app_id,
+            mods,
+            trigger,
+            sequence,
+            sequence_timeout,
+            key,
+            program,
+            arguments,
+        } => {
+            let app_id = app_id_matcher(app_id)?;
+
+            let sequence_timeout =
+                sequence_timeout.map_or(DEFAULT_SEQUENCE_TIMEOUT, Duration::from_millis);
             let binding = KeyBinding {
+                sequence_timeout,
                 arguments,
+                sequence,
                 program,
                 trigger,
                 app_id,
@@ -142,14 +294,17 @@ fn handle_message(buffer: &mut String, mut stream: UnixStream, catacomb: &mut Ca
             };
             catacomb.key_bindings.push(binding);
         },
-        IpcMessage::UnbindKey { app_id, mods, key } => {
+        IpcMessage::UnbindKey { app_id, mods, sequence, key } => {
             let mods = mods.unwrap_or_default();
 
             catacomb.key_bindings.retain(|binding| {
-                binding.app_id.base() != app_id || binding.key != key || binding.mods != mods
+                binding.app_id.base() != app_id
+                    || binding.key != key
+                    || binding.mods != mods
+                    || binding.sequence != sequence
             });
         },
-        IpcMessage::KeyboardConfig { layout, options } => {
+        IpcMessage::KeyboardConfig { model, layout, variant, options } => {
             let layout = layout.as_deref().unwrap_or_default();
             let model = model.as_deref().unwrap_or_default();
             let variant = variant.as_deref().unwrap_or_default();
@@ -161,18 +316,311 @@ fn handle_message(buffer: &mut String, mut stream: UnixStream, catacomb: &mut Ca
         },
         IpcMessage::Dpms { state: None } => {
             let state = if catacomb.display_on { CliToggle::On } else { CliToggle::Off };
-            send_reply(&mut stream, &IpcMessage::DpmsReply { state });
+            return Ok(Some(IpcMessage::DpmsReply { state }));
         },
         IpcMessage::Cursor { state } => {
             catacomb.draw_cursor = state == CliToggle::On;
         },
-        // Ignore IPC replies.
-        IpcMessage::DpmsReply { .. } => (),
+        IpcMessage::Windows => {
+            let windows = catacomb.windows.window_info();
+            return Ok(Some(IpcMessage::WindowsReply { windows }));
+        },
+        IpcMessage::ListBindings => {
+            let bindings = catacomb.key_bindings.iter().map(KeyBinding::info).collect();
+            return Ok(Some(IpcMessage::BindingsReply { bindings }));
+        },
+        IpcMessage::ListGestures => {
+            let gestures =
+                catacomb.touch_state.user_gestures.iter().map(GestureBinding::info).collect();
+            return Ok(Some(IpcMessage::GesturesReply { gestures }));
+        },
+        IpcMessage::ListTouchGestures => {
+            let gestures = catacomb.touch_state.touch_gestures.iter();
+            let gestures = gestures.map(TouchGestureBinding::info).collect();
+            return Ok(Some(IpcMessage::TouchGesturesReply { gestures }));
+        },
+        IpcMessage::ListEdgeSwipes => {
+            let swipes = catacomb.touch_state.edge_swipes.iter().map(EdgeSwipeBinding::info);
+            return Ok(Some(IpcMessage::EdgeSwipesReply { swipes: swipes.collect() }));
+        },
+        IpcMessage::ListTapGestures => {
+            let gestures = catacomb.touch_state.tap_gestures.iter();
+            let gestures = gestures.map(TapGestureBinding::info).collect();
+            return Ok(Some(IpcMessage::TapGesturesReply { gestures }));
+        },
+        IpcMessage::ListScales => {
+            let scales = catacomb
+                .windows
+                .window_rules
+                .iter()
+                .filter_map(|(app_id, rule)| {
+                    Some(WindowScaleInfo { app_id: app_id.base().into(), scale: rule.scale? })
+                })
+                .collect();
+            return Ok(Some(IpcMessage::ScalesReply { scales }));
+        },
+        IpcMessage::Rule {
+            app_id,
+            fullscreen,
+            deny_focus,
+            orientation,
+            no_thumbnail,
+            placement,
+        } => {
+            let app_id = app_id_matcher(app_id)?;
+            let rule = WindowRule {
+                fullscreen,
+                deny_focus,
+                orientation,
+                no_thumbnail,
+                placement,
+                ..WindowRule::default()
+            };
+            catacomb.windows.set_window_rule(app_id, rule);
+            catacomb.windows.set_dirty();
+            catacomb.unstall();
+        },
+        IpcMessage::ListRules => {
+            let rules = catacomb
+                .windows
+                .window_rules
+                .iter()
+                .map(|(app_id, rule)| WindowRuleInfo { app_id: app_id.base().into(), rule: *rule })
+                .collect();
+            return Ok(Some(IpcMessage::RulesReply { rules }));
+        },
+        IpcMessage::Window { action, app_id, id } => {
+            if app_id.is_none() && id.is_none() {
+                let message = "window App ID or ID required";
+                return Err(IpcError::new(ErrorKind::InvalidMessage, message));
+            }
+
+            let app_id = app_id.map(app_id_matcher).transpose()?;
+            let surface = match catacomb.windows.find_toplevel(app_id.as_ref(), id) {
+                Some(surface) => surface,
+                None => return Err(IpcError::new(ErrorKind::WindowNotFound, "no matching window")),
+            };
+
+            match action {
+                WindowAction::Focus => catacomb.windows.focus_toplevel(&surface),
+                WindowAction::Fullscreen => catacomb.windows.toggle_fullscreen(&surface),
+                WindowAction::Primary => catacomb.windows.set_primary(&surface),
+                WindowAction::Secondary => catacomb.windows.set_secondary(&surface),
+                WindowAction::Close => surface.send_close(),
+                WindowAction::Kill => catacomb.windows.kill(&surface),
+            }
+
+            catacomb.windows.set_dirty();
+            catacomb.unstall();
+        },
+        IpcMessage::Layout { action: LayoutAction::Activate { index } } => {
+            if !catacomb.windows.set_active_layout(index) {
+                let message = format!("no layout at index {index}");
+                return Err(IpcError::new(ErrorKind::LayoutNotFound, message));
+            }
+
+            catacomb.windows.set_dirty();
+            catacomb.unstall();
+        },
+        IpcMessage::Layout { action: LayoutAction::Cycle { n } } => {
+            catacomb.windows.cycle_active_layout(n);
+            catacomb.windows.set_dirty();
+            catacomb.unstall();
+        },
+        IpcMessage::Layout { action: LayoutAction::Swap } => {
+            catacomb.windows.swap_active_layout();
+            catacomb.windows.set_dirty();
+            catacomb.unstall();
+        },
+        IpcMessage::Version => {
+            let requests = catacomb_ipc::REQUESTS.iter().map(|request| request.to_string());
+            return Ok(Some(IpcMessage::VersionReply {
+                version: env!("CARGO_PKG_VERSION").into(),
+                protocol: catacomb_ipc::PROTOCOL_VERSION,
+                requests: requests.collect(),
+            }));
+        },
+        IpcMessage::Reload => catacomb.reload_config(),
+        IpcMessage::Outputs => {
+            let outputs = match catacomb.backend.output_modes() {
+                Some(modes) => vec![catacomb.windows.output().info(&modes)],
+                None => Vec::new(),
+            };
+            return Ok(Some(IpcMessage::OutputsReply { outputs }));
+        },
+        IpcMessage::SetMode { width, height, refresh } => {
+            let modes = match catacomb.backend.output_modes() {
+                Some(modes) => modes,
+                None => {
+                    return Err(IpcError::new(ErrorKind::OutputNotFound, "no output connected"));
+                },
+            };
+
+            // Fill in missing values from the current mode.
+            let current_mode = catacomb.windows.output().mode();
+            let width = width.unwrap_or(current_mode.size.w);
+            let height = height.unwrap_or(current_mode.size.h);
+
+            // Pick the highest refresh rate if none was specified.
+            let mode = modes
+                .into_iter()
+                .filter(|mode| mode.size.w == width && mode.size.h == height)
+                .filter(|mode| refresh.is_none_or(|refresh| mode.refresh == refresh * 1000))
+                .max_by_key(|mode| mode.refresh);
+            let mode = match mode {
+                Some(mode) => mode,
+                None => {
+                    let refresh =
+                        refresh.map(|refresh| format!("@{refresh}Hz")).unwrap_or_default();
+                    let message = format!("no output mode {width}x{height}{refresh}");
+                    return Err(IpcError::new(ErrorKind::ModeNotFound, message));
+                },
+            };
+
+            if let Err(err) = catacomb.backend.set_mode(&mut catacomb.windows, mode) {
+                return Err(IpcError::new(ErrorKind::ModeChangeFailed, err.to_string()));
+            }
+
+            catacomb.unstall();
+        },
+        IpcMessage::InputConfig { device, settings } if settings == InputSettings::default() => {
+            let mut devices = catacomb.backend.input_devices();
+            if let Some(device) = device {
+                devices.retain(|info| info.name == device);
+            }
+            return Ok(Some(IpcMessage::InputConfigReply { devices }));
+        },
+        IpcMessage::InputConfig { device: Some(device), settings } => {
+            if let Err(err) = settings.validate() {
+                return Err(IpcError::new(ErrorKind::InvalidMessage, err));
+            }
+
+            if let Some(calibration) = &settings.calibration {
+                if let Err(err) = udev::calibration_matrix(calibration) {
+                    let message = format!("invalid calibration matrix: {err}");
+                    return Err(IpcError::new(ErrorKind::InvalidMatrix, message));
+                }
+            }
+
+            if let Err(err) = catacomb.backend.set_input_settings(device, settings) {
+                return Err(IpcError::new(ErrorKind::InvalidMessage, err));
+            }
+        },
+        IpcMessage::InputConfig { device: None, .. } => {
+            let message = "input device name required";
+            return Err(IpcError::new(ErrorKind::InvalidMessage, message));
+        },
+        IpcMessage::Timings { timings } if timings == InputTimings::default() => {
+            let timings = catacomb.touch_state.timings.ipc_timings();
+            return Ok(Some(IpcMessage::TimingsReply { timings }));
+        },
+        IpcMessage::Timings { timings } => {
+            if let Err(err) = timings.validate() {
+                return Err(IpcError::new(ErrorKind::InvalidMessage, err));
+            }
+
+            let mut new_timings = catacomb.touch_state.timings;
+            new_timings.update(&timings);
+            catacomb.set_timings(new_timings);
+        },
+        IpcMessage::Theme { theme } if theme == ThemeSettings::default() => {
+            let mut theme = catacomb.backend.theme().ipc_theme();
+            theme.gesture_handle_height =
+                Some(catacomb.windows.canvas().gesture_handle_height() as u32);
+            return Ok(Some(IpcMessage::ThemeReply { theme }));
+        },
+        IpcMessage::Theme { theme: settings } => {
+            if let Err(err) = settings.validate() {
+                return Err(IpcError::new(ErrorKind::InvalidMessage, err));
+            }
+
+            if let Some(height) = settings.gesture_handle_height {
+                catacomb.windows.set_gesture_handle_height(height as i32);
+            }
+
+            let mut theme = catacomb.backend.theme();
+            theme.update(&settings);
+            catacomb.backend.set_theme(theme);
+
+            catacomb.force_redraw(false);
+        },
+        IpcMessage::Screenshot { path, region, include_cursor } => {
+            let cursor_position = catacomb.touch_state.position().filter(|_| include_cursor);
+            let region = region.map(Rectangle::from);
+            let result =
+                catacomb.backend.screenshot(&mut catacomb.windows, cursor_position, region, &path);
+            if let Err(err) = result {
+                let message = format!("could not save screenshot to {path:?}: {err}");
+                return Err(IpcError::new(ErrorKind::ScreenshotFailed, message));
+            }
+        },
+        IpcMessage::Record { pipe, target, arguments } => {
+            let target = if pipe {
+                RecordingTarget::Pipe(target, arguments)
+            } else {
+                RecordingTarget::File(target)
+            };
+
+            if let Err(err) = catacomb.backend.start_recording(&catacomb.windows, target) {
+                let message = format!("could not start recording: {err}");
+                return Err(IpcError::new(ErrorKind::RecordingFailed, message));
+            }
+
+            // Ensure the current frame is recorded.
+            catacomb.force_redraw(true);
+        },
+        IpcMessage::StopRecording => {
+            if !catacomb.backend.stop_recording() {
+                return Err(IpcError::new(ErrorKind::RecordingFailed, "no active recording"));
+            }
+        },
+        IpcMessage::IdleTimeout { timeout: 0, .. } => {
+            return Err(IpcError::new(ErrorKind::InvalidMessage, "idle timeout must not be zero"));
+        },
+        IpcMessage::IdleTimeout { timeout, action } => {
+            catacomb.idle_manager.add(Duration::from_secs(timeout), action);
+        },
+        IpcMessage::ClearIdleTimeouts => catacomb.idle_manager.clear(),
+        IpcMessage::ListIdleTimeouts => {
+            let timeouts = catacomb.idle_manager.ipc_timeouts();
+            return Ok(Some(IpcMessage::IdleTimeoutsReply { timeouts }));
+        },
+        // Subscriptions are handled before dispatching requests.
+        IpcMessage::Subscribe { .. } => (),
+        // Reject IPC replies.
+        IpcMessage::DpmsReply { .. }
+        | IpcMessage::WindowsReply { .. }
+        | IpcMessage::BindingsReply { .. }
+        | IpcMessage::GesturesReply { .. }
+        | IpcMessage::TouchGesturesReply { .. }
+        | IpcMessage::EdgeSwipesReply { .. }
+        | IpcMessage::TapGesturesReply { .. }
+        | IpcMessage::ScalesReply { .. }
+        | IpcMessage::VersionReply { .. }
+        | IpcMessage::OutputsReply { .. }
+        | IpcMessage::RulesReply { .. }
+        | IpcMessage::InputConfigReply { .. }
+        | IpcMessage::TimingsReply { .. }
+        | IpcMessage::ThemeReply { .. }
+        | IpcMessage::IdleTimeoutsReply { .. }
+        | IpcMessage::Ok
+        | IpcMessage::Error { .. } => {
+            return Err(IpcError::new(ErrorKind::InvalidMessage, "unexpected reply message"));
+        },
     }
+
+    Ok(None)
+}
+
+/// Parse an App ID matcher for an IPC request.
+fn app_id_matcher(app_id: String) -> Result<AppIdMatcher, IpcError> {
+    AppIdMatcher::try_from(app_id).map_err(|err| {
+        IpcError::new(ErrorKind::InvalidAppId, format!("invalid App ID regex: {err}"))
+    })
 }
 
 /// Send IPC message reply.
-fn send_reply(stream: &mut UnixStream, message: &IpcMessage) {
+fn send_reply(stream: &UnixStream, message: &IpcMessage) {
     if let Err(err) = send_reply_fallible(stream, message) {
         error!("Could not send IPC reply: {err}");
     }
@@ -180,11 +628,257 @@ fn send_reply(stream: &mut UnixStream, message: &IpcMessage) {
 
 /// Send IPC message reply, returning possible errors.
 fn send_reply_fallible(
-    stream: &mut UnixStream,
+    mut stream: &UnixStream,
     message: &IpcMessage,
 ) -> Result<(), Box<dyn Error>> {
-    let json = serde_json::to_string(&message)?;
+    let mut json = serde_json::to_string(&message)?;
+    json.push('\n');
     stream.write_all(json.as_bytes())?;
     stream.flush()?;
     Ok(())
 }
+
+/// Send pending compositor events to all IPC subscribers.
+pub fn dispatch_events(catacomb: &mut Catacomb) {
+    let windows_dirty = catacomb.windows.take_ipc_dirty();
+
+    // Avoid tracking state without any subscribers.
+    if catacomb.ipc_subscribers.subscribers.is_empty() {
+        catacomb.ipc_subscribers.state = None;
+        return;
+    }
+
+    // Only rebuild the state after relevant changes.
+    if !mem::take(&mut catacomb.ipc_subscribers.dirty) && !windows_dirty {
+        return;
+    }
+
+    let state = IpcState::new(catacomb);
+    let subscribers = &mut catacomb.ipc_subscribers;
+    let events = subscribers.state.as_ref().map(|old| old.diff(&state)).unwrap_or_default();
+    subscribers.state = Some(state);
+
+    for event in &events {
+        subscribers.subscribers.retain_mut(|subscriber| subscriber.send(event));
+    }
+}
+
+/// IPC event subscribers.
+#[derive(Default, Debug)]
+pub struct IpcSubscribers {
+    subscribers: Vec<Subscriber>,
+    state: Option<IpcState>,
+    dirty: bool,
+}
+
+impl IpcSubscribers {
+    /// Add a new event subscriber.
+    ///
+    /// Subscribers are written to without blocking, and dropped once their
+    /// socket buffer is full.
+    fn add(&mut self, stream: UnixStream, events: Vec<EventKind>) -> io::Result<()> {
+        stream.set_nonblocking(true)?;
+        self.subscribers.push(Subscriber { stream, events });
+        self.dirty = true;
+        Ok(())
+    }
+
+    /// Mark the IPC state as outdated.
+    pub fn mark_dirty(&mut self) {
+        self.dirty = true;
+    }
+}
+
+/// IPC event stream.
+#[derive(Debug)]
+struct Subscriber {
+    stream: UnixStream,
+    events: Vec<EventKind>,
+}
+
+impl Subscriber {
+    /// Send an event to the subscriber.
+    ///
+    /// Returns `false` if the subscriber's connection is dead.
+    fn send(&mut self, event: &IpcEvent) -> bool {
+        if !self.events.is_empty() && !self.events.contains(&event.kind()) {
+            return true;
+        }
+
+        match send_event(&mut self.stream, event) {
+            Ok(()) => true,
+            Err(err) => {
+                warn!("Dropping IPC subscriber: {err}");
+                let _ = self.stream.shutdown(Shutdown::Both);
+                false
+            },
+        }
+    }
+}
+
+/// Write a single event to an IPC stream.
+fn send_event(stream: &mut UnixStream, event: &IpcEvent) -> Result<(), Box<dyn Error>> {
+    let mut json = serde_json::to_string(event)?;
+    json.push('\n');
+    stream.write_all(json.as_bytes())?;
+    stream.flush()?;
+    Ok(())
+}
+
+/// Compositor state observable by IPC subscribers.
+#[derive(Debug)]
+struct IpcState {
+    windows: Vec<WindowInfo>,
+    active_layout: Option<usize>,
+    orientation: Orientation,
+    display_on: bool,
+    scale: f64,
+}
+
+impl IpcState {
+    /// Capture the current compositor state.
+    fn new(catacomb: &Catacomb) -> Self {
+        let canvas = catacomb.windows.canvas();
+        Self {
+            windows: catacomb.windows.window_info(),
+            active_layout: catacomb.windows.active_layout(),
+            orientation: canvas.orientation(),
+            display_on: catacomb.display_on,
+            scale: canvas.scale(),
+        }
+    }
+
+    /// Get events required to transition from this state to `new`.
+    fn diff(&self, new: &Self) -> Vec<IpcEvent> {
+        let mut events = Vec::new();
+
+        // Report created and destroyed windows.
+        let mut windows_changed = false;
+        for window in &new.windows {
+            if !self.windows.iter().any(|old| old.id == window.id) {
+                events.push(IpcEvent::WindowOpen { window: window.clone() });
+                windows_changed = true;
+            }
+        }
+        for window in &self.windows {
+            if !new.windows.iter().any(|new| new.id == window.id) {
+                events.push(IpcEvent::WindowClose { window: window.clone() });
+                windows_changed = true;
+            }
+        }
+
+        // Report changes to windows present in both states.
+        for window in &new.windows {
+            let old = match self.windows.iter().find(|old| old.id == window.id) {
+                Some(old) => old,
+                None => continue,
+            };
+
+            if old.urgent != window.urgent {
+                events.push(IpcEvent::Urgency { window: window.clone() });
+            }
+
+            windows_changed |= old.layout != window.layout || old.position != window.position;
+        }
+
+        let old_focus = self.windows.iter().find(|window| window.focused);
+        let new_focus = new.windows.iter().find(|window| window.focused);
+        if old_focus.map(|window| window.id) != new_focus.map(|window| window.id) {
+            events.push(IpcEvent::Focus { window: new_focus.cloned() });
+        }
+
+        if windows_changed || self.active_layout != new.active_layout {
+            events.push(IpcEvent::Layout { active: new.active_layout });
+        }
+
+        if self.orientation != new.orientation {
+            events.push(IpcEvent::Orientation { orientation: new.orientation });
+        }
+
+        if self.display_on != new.display_on {
+            let state = if new.display_on { CliToggle::On } else { CliToggle::Off };
+            events.push(IpcEvent::Dpms { state });
+        }
+
+        if self.scale != new.scale {
+            events.push(IpcEvent::Scale { scale: new.scale });
+        }
+
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use catacomb_ipc::{Geometry, LayoutSlot};
+
+    use super::*;
+
+    #[test]
+    fn state_diff() {
+        let old = state(vec![window(1, 0, true, false), window(2, 1, false, false)]);
+
+        // Unchanged state produces no events.
+        assert_eq!(old.diff(&old), Vec::new());
+
+        // Opening a window reports the window, focus and layout change.
+        let new = state(vec![
+            window(1, 0, false, false),
+            window(2, 1, false, false),
+            window(3, 2, true, false),
+        ]);
+        assert_eq!(old.diff(&new), vec![
+            IpcEvent::WindowOpen { window: window(3, 2, true, false) },
+            IpcEvent::Focus { window: Some(window(3, 2, true, false)) },
+            IpcEvent::Layout { active: Some(0) },
+        ]);
+
+        // Closing the focused window clears focus.
+        let new = state(vec![window(2, 1, false, false)]);
+        assert_eq!(old.diff(&new), vec![
+            IpcEvent::WindowClose { window: window(1, 0, true, false) },
+            IpcEvent::Focus { window: None },
+            IpcEvent::Layout { active: Some(0) },
+        ]);
+
+        // Urgency changes do not affect the layout.
+        let new = state(vec![window(1, 0, true, false), window(2, 1, false, true)]);
+        assert_eq!(old.diff(&new), vec![IpcEvent::Urgency { window: window(2, 1, false, true) }]);
+
+        // Output changes are reported individually.
+        let mut new = state(old.windows.clone());
+        new.orientation = Orientation::Landscape;
+        new.display_on = false;
+        new.scale = 2.;
+        assert_eq!(old.diff(&new), vec![
+            IpcEvent::Orientation { orientation: Orientation::Landscape },
+            IpcEvent::Dpms { state: CliToggle::Off },
+            IpcEvent::Scale { scale: 2. },
+        ]);
+    }
+
+    fn state(windows: Vec<WindowInfo>) -> IpcState {
+        IpcState {
+            windows,
+            active_layout: Some(0),
+            orientation: Orientation::Portrait,
+            display_on: true,
+            scale: 1.,
+        }
+    }
+
+    fn window(id: u64, layout: usize, focused: bool, urgent: bool) -> WindowInfo {
+        WindowInfo {
+            id,
+            layout,
+            focused,
+            urgent,
+            app_id: None,
+            title: None,
+            position: LayoutSlot::Primary,
+            fullscreen: false,
+            scale: 1.,
+            rectangle: Geometry::default(),
+        }
+    }
+}
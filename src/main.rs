@@ -2,9 +2,9 @@ use std::ffi::OsStr;
 use std::mem::MaybeUninit;
 use std::os::unix::process::CommandExt;
 use std::process::{Command, Stdio};
-use std::{env, io, ptr};
+use std::{env, io, path, process, ptr};
 
-use catacomb_ipc::{CliToggle, IpcMessage};
+use catacomb_ipc::{CliToggle, ConnectionError, ErrorKind, IpcError, IpcMessage};
 use clap::{Parser, Subcommand};
 #[cfg(feature = "profiling")]
 use profiling::puffin;
@@ -17,6 +17,7 @@ mod config;
 mod daemon;
 mod drawing;
 mod geometry;
+mod idle;
 mod input;
 mod ipc_server;
 mod layer;
@@ -24,6 +25,7 @@ mod orientation;
 mod output;
 mod overview;
 mod protocols;
+mod recording;
 mod socket;
 mod udev;
 mod windows;
@@ -56,27 +58,131 @@ pub fn main() {
     FmtSubscriber::builder().with_env_filter(env_filter).with_line_number(true).init();
 
     match Options::parse().subcommands {
-        Some(Subcommands::Msg(msg)) => match catacomb_ipc::send_message(&msg) {
-            Err(err) => eprintln!("\x1b[31merror\x1b[0m: {err}"),
-            Ok(Some(IpcMessage::DpmsReply { state: CliToggle::On })) => println!("on"),
-            Ok(Some(IpcMessage::DpmsReply { state: CliToggle::Off })) => println!("off"),
-            Ok(_) => (),
+        Some(Subcommands::Msg(msg)) => {
+            if let Err(err) = send_message(msg) {
+                eprintln!("\x1b[31merror\x1b[0m: {err}");
+
+                let exit_code = match err {
+                    ConnectionError::MissingDisplay => 101,
+                    ConnectionError::SocketNotFound(_) => 102,
+                    // Check for version mismatch if the compositor didn't understand us.
+                    ConnectionError::Request(IpcError {
+                        kind: ErrorKind::InvalidMessage, ..
+                    }) => {
+                        check_protocol_version();
+                        1
+                    },
+                    _ => 1,
+                };
+                process::exit(exit_code);
+            }
         },
         None => udev::run(),
     }
 }
 
+/// Send an IPC message and print its reply.
+fn send_message(mut msg: IpcMessage) -> Result<(), ConnectionError> {
+    // Resolve paths relative to the client's working directory.
+    match &mut msg {
+        IpcMessage::Screenshot { path, .. }
+        | IpcMessage::Record { pipe: false, target: path, .. } => {
+            *path = path::absolute(&*path)?;
+        },
+        _ => (),
+    }
+
+    // Print events until the compositor closes the connection.
+    if let IpcMessage::Subscribe { events } = msg {
+        for event in catacomb_ipc::subscribe(events)? {
+            println!("{}", serde_json::to_string(&event?)?);
+        }
+        return Ok(());
+    }
+
+    match catacomb_ipc::send_message(&msg)? {
+        Some(IpcMessage::DpmsReply { state: CliToggle::On }) => println!("on"),
+        Some(IpcMessage::DpmsReply { state: CliToggle::Off }) => println!("off"),
+        Some(IpcMessage::WindowsReply { windows }) => {
+            println!("{}", serde_json::to_string_pretty(&windows)?);
+        },
+        Some(IpcMessage::BindingsReply { bindings }) => {
+            println!("{}", serde_json::to_string_pretty(&bindings)?);
+        },
+        Some(IpcMessage::GesturesReply { gestures }) => {
+            println!("{}", serde_json::to_string_pretty(&gestures)?);
+        },
+        Some(IpcMessage::TouchGesturesReply { gestures }) => {
+            println!("{}", serde_json::to_string_pretty(&gestures)?);
+        },
+        Some(IpcMessage::EdgeSwipesReply { swipes }) => {
+            println!("{}", serde_json::to_string_pretty(&swipes)?);
+        },
+        Some(IpcMessage::TapGesturesReply { gestures }) => {
+            println!("{}", serde_json::to_string_pretty(&gestures)?);
+        },
+        Some(IpcMessage::ScalesReply { scales }) => {
+            println!("{}", serde_json::to_string_pretty(&scales)?);
+        },
+        Some(IpcMessage::InputConfigReply { devices }) => {
+            println!("{}", serde_json::to_string_pretty(&devices)?);
+        },
+        Some(IpcMessage::TimingsReply { timings }) => {
+            println!("{}", serde_json::to_string_pretty(&timings)?);
+        },
+        Some(IpcMessage::ThemeReply { theme }) => {
+            println!("{}", serde_json::to_string_pretty(&theme)?);
+        },
+        Some(IpcMessage::IdleTimeoutsReply { timeouts }) => {
+            println!("{}", serde_json::to_string_pretty(&timeouts)?);
+        },
+        Some(IpcMessage::RulesReply { rules }) => {
+            println!("{}", serde_json::to_string_pretty(&rules)?);
+        },
+        Some(IpcMessage::OutputsReply { outputs }) => {
+            println!("{}", serde_json::to_string_pretty(&outputs)?);
+        },
+        Some(IpcMessage::VersionReply { version, protocol, requests }) => {
+            println!("compositor: {version}");
+            println!("protocol: {protocol}");
+            println!("requests: {}", requests.join(", "));
+        },
+        _ => (),
+    }
+
+    Ok(())
+}
+
+/// Report IPC protocol mismatch between client and compositor.
+fn check_protocol_version() {
+    let protocol = match catacomb_ipc::send_message(&IpcMessage::Version) {
+        Ok(Some(IpcMessage::VersionReply { protocol, .. })) => protocol,
+        _ => {
+            eprintln!("Compositor does not support version queries, it might be outdated");
+            return;
+        },
+    };
+
+    if protocol != catacomb_ipc::PROTOCOL_VERSION {
+        eprintln!(
+            "IPC protocol mismatch: compositor uses version {protocol}, client uses version {}",
+            catacomb_ipc::PROTOCOL_VERSION
+        );
+    }
+}
+
 /// Spawn unsupervised daemons.
 ///
 /// This will double-fork to avoid spawning zombies, but does not provide any
 /// ability to retrieve the process output.
-pub fn daemon<I, S>(program: S, args: I) -> io::Result<()>
+pub fn daemon<I, S>(program: S, args: I, envs: &[(&str, &str)]) -> io::Result<()>
 where
     I: IntoIterator<Item = S>,
     S: AsRef<OsStr>,
 {
     let mut command = Command::new(program);
     command.args(args);
+    command.envs(envs.iter().copied());
     command.stdin(Stdio::null());
     command.stdout(Stdio::null());
     command.stderr(Stdio::null());
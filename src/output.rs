@@ -3,6 +3,7 @@
 use std::ops::{Add, Deref, Sub};
 use std::time::Duration;
 
+use catacomb_ipc::{OutputInfo, OutputMode};
 use smithay::output::{
     Mode, Output as SmithayOutput, OutputModeSource, PhysicalProperties, Scale, Subpixel,
 };
@@ -15,7 +16,8 @@ use smithay::wayland::shell::wlr_layer::{Anchor, ExclusiveZone, Layer};
 use crate::catacomb::Catacomb;
 use crate::orientation::Orientation;
 
-/// Height at bottom of the screen reserved for gestures at scale factor 1.
+/// Default height at bottom of the screen reserved for gestures at scale
+/// factor 1.
 pub const GESTURE_HANDLE_HEIGHT: i32 = 15;
 
 /// Use a fixed output scale.
@@ -119,6 +121,11 @@ impl Output {
         }
     }
 
+    /// Update the height reserved for the gesture handle.
+    pub fn set_gesture_handle_height(&mut self, height: i32) {
+        self.canvas.gesture_handle_height = height;
+    }
+
     /// Update the device orientation.
     pub fn set_orientation(&mut self, orientation: Orientation) {
         self.canvas.orientation = orientation;
@@ -151,6 +158,20 @@ impl Output {
     pub fn smithay_output(&self) -> &SmithayOutput {
         &self.output
     }
+
+    /// Get IPC state of the output.
+    pub fn info(&self, modes: &[Mode]) -> OutputInfo {
+        let physical_size = self.output.physical_properties().size;
+        OutputInfo {
+            name: self.output.name(),
+            physical_width: physical_size.w,
+            physical_height: physical_size.h,
+            modes: modes.iter().copied().map(output_mode).collect(),
+            current_mode: output_mode(self.canvas.mode),
+            scale: self.canvas.scale,
+            orientation: self.canvas.orientation,
+        }
+    }
 }
 
 impl Deref for Output {
@@ -166,14 +187,25 @@ impl Deref for Output {
 pub struct Canvas {
     exclusive: ExclusiveSpace,
     orientation: Orientation,
+    gesture_handle_height: i32,
     scale: f64,
     mode: Mode,
 }
 
 impl Canvas {
     fn new(mode: Mode) -> Self {
-        let scale = SCALE;
-        Self { mode, scale, orientation: Default::default(), exclusive: Default::default() }
+        Self {
+            mode,
+            scale: SCALE,
+            gesture_handle_height: GESTURE_HANDLE_HEIGHT,
+            orientation: Default::default(),
+            exclusive: Default::default(),
+        }
+    }
+
+    /// Height at bottom of the screen reserved for gestures.
+    pub fn gesture_handle_height(&self) -> i32 {
+        self.gesture_handle_height
     }
 
     /// Device orientation.
@@ -181,6 +213,11 @@ impl Canvas {
         self.orientation
     }
 
+    /// Active output mode.
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
     /// Output device resolution in physical coordinates.
     pub fn physical_resolution(&self) -> Size<i32, Physical> {
         self.mode.size
@@ -216,7 +253,7 @@ impl Canvas {
     /// for compositor controls.
     pub fn wm_size(&self) -> Size<i32, Logical> {
         let mut size = self.size();
-        size.h -= GESTURE_HANDLE_HEIGHT;
+        size.h -= self.gesture_handle_height;
         size
     }
 
@@ -300,6 +337,11 @@ impl From<&Canvas> for OutputModeSource {
     }
 }
 
+/// Convert a Wayland output mode to its IPC representation.
+fn output_mode(mode: Mode) -> OutputMode {
+    OutputMode { width: mode.size.w, height: mode.size.h, refresh: mode.refresh / 1000 }
+}
+
 /// Output space reserved by layer shell surfaces.
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 pub struct ExclusiveSpace {
@@ -3,6 +3,7 @@
 use std::cell::RefCell;
 use std::cmp;
 use std::rc::{Rc, Weak};
+use std::time::Duration;
 
 use smithay::reexports::calloop::timer::{TimeoutAction, Timer};
 use smithay::reexports::calloop::{LoopHandle, RegistrationToken};
@@ -11,7 +12,6 @@ use smithay::utils::{Logical, Point, Rectangle, Size};
 use crate::catacomb::Catacomb;
 use crate::drawing::{CatacombElement, Graphics};
 use crate::geometry::Vector;
-use crate::input::HOLD_DURATION;
 use crate::output::{Canvas, Output};
 use crate::windows::layout::{LayoutPosition, Layouts};
 use crate::windows::window::Window;
@@ -115,12 +115,13 @@ impl Overview {
         &mut self,
         event_loop: &LoopHandle<'static, Catacomb>,
         layout_position: LayoutPosition,
+        hold_duration: Duration,
     ) {
         // Ensure no timer is currently active.
         self.cancel_hold(event_loop);
 
         // Start a new timer.
-        let timer = Timer::from_duration(HOLD_DURATION);
+        let timer = Timer::from_duration(hold_duration);
         let hold_timer = event_loop
             .insert_source(timer, move |_, _, catacomb| {
                 catacomb.windows.start_dnd(layout_position);
@@ -300,6 +301,29 @@ impl Overview {
             );
         }
 
+        // Replace window content with a placeholder if thumbnails are disabled.
+        if window.rule().no_thumbnail == Some(true) {
+            let texture = graphics.thumbnail_placeholder.clone();
+
+            // Scale placeholder texture to cover the window.
+            let bounds = match bounds.to_physical_precise_round(scale).intersection(physical_bounds)
+            {
+                Some(bounds) => bounds,
+                None => return,
+            };
+            let window_scale = Some(cmp::max(bounds.size.w, bounds.size.h) as f64);
+
+            CatacombElement::add_element(
+                textures,
+                texture,
+                bounds.loc,
+                bounds,
+                window_scale,
+                scale,
+            );
+            return;
+        }
+
         // Get window textures within its overview bounds.
         window.textures_with_bounds(textures, scale, position.scale, bounds.loc, physical_bounds);
     }
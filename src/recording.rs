@@ -0,0 +1,217 @@
+//! Screen recording.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, OnceLock};
+use std::thread;
+use std::time::Instant;
+
+use smithay::utils::{Physical, Size};
+use tracing::{error, warn};
+
+/// Maximum number of frames waiting for the encoder thread.
+const MAX_QUEUED_FRAMES: usize = 4;
+
+/// Fallback frame rate in mHz for outputs without refresh rate.
+const DEFAULT_REFRESH: i32 = 60_000;
+
+/// Recording destination.
+#[derive(Debug)]
+pub enum RecordingTarget {
+    /// Write video to a file.
+    File(PathBuf),
+    /// Pipe video to an encoder's STDIN.
+    Pipe(PathBuf, Vec<String>),
+}
+
+/// Active YUV4MPEG2 screen recording.
+///
+/// Frames are converted and written on a separate thread, the recording is
+/// finished once this is dropped.
+#[derive(Debug)]
+pub struct Recording {
+    sender: SyncSender<Frame>,
+    stop_time: Arc<OnceLock<Instant>>,
+    size: Size<i32, Physical>,
+}
+
+impl Recording {
+    /// Start a new recording.
+    ///
+    /// The `refresh` rate is in mHz, like Wayland output modes.
+    pub fn new(
+        target: RecordingTarget,
+        size: Size<i32, Physical>,
+        refresh: i32,
+    ) -> io::Result<Self> {
+        let (writer, child): (Box<dyn Write + Send>, _) = match target {
+            RecordingTarget::File(path) => (Box::new(File::create(path)?), None),
+            RecordingTarget::Pipe(program, arguments) => {
+                let mut child = Command::new(program)
+                    .args(arguments)
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::null())
+                    .spawn()?;
+                (Box::new(child.stdin.take().unwrap()), Some(child))
+            },
+        };
+
+        let refresh = if refresh > 0 { refresh } else { DEFAULT_REFRESH };
+        let stop_time = Arc::new(OnceLock::new());
+        let encoder = Encoder {
+            writer: BufWriter::new(writer),
+            stop_time: stop_time.clone(),
+            size,
+            refresh,
+            last_frame: Default::default(),
+            frame_count: Default::default(),
+            start: Default::default(),
+        };
+
+        let (sender, receiver) = mpsc::sync_channel(MAX_QUEUED_FRAMES);
+        thread::Builder::new()
+            .name("recording".into())
+            .spawn(move || encoder.run(receiver, child))?;
+
+        Ok(Self { sender, stop_time, size })
+    }
+
+    /// Physical size of the recorded frames.
+    pub fn size(&self) -> Size<i32, Physical> {
+        self.size
+    }
+
+    /// Queue an RGBA frame rendered at `time` for encoding.
+    ///
+    /// Returns `false` if the encoder has stopped.
+    pub fn add_frame(&self, time: Instant, pixels: Vec<u8>) -> bool {
+        match self.sender.try_send(Frame { time, pixels }) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                warn!("Dropping recording frame, encoder is too slow");
+                true
+            },
+            Err(TrySendError::Disconnected(_)) => false,
+        }
+    }
+}
+
+impl Drop for Recording {
+    fn drop(&mut self) {
+        // Let the encoder know how long the last frame was visible.
+        let _ = self.stop_time.set(Instant::now());
+    }
+}
+
+/// Rendered RGBA frame.
+struct Frame {
+    pixels: Vec<u8>,
+    time: Instant,
+}
+
+/// YUV4MPEG2 video encoder.
+struct Encoder {
+    writer: BufWriter<Box<dyn Write + Send>>,
+    stop_time: Arc<OnceLock<Instant>>,
+    size: Size<i32, Physical>,
+    refresh: i32,
+
+    last_frame: Vec<u8>,
+    start: Option<Instant>,
+    frame_count: u64,
+}
+
+impl Encoder {
+    /// Encode frames until the recording is stopped.
+    fn run(mut self, receiver: Receiver<Frame>, child: Option<Child>) {
+        if let Err(err) = self.encode(receiver) {
+            error!("Screen recording failed: {err}");
+        }
+
+        // Close STDIN to let the encoder finish.
+        drop(self.writer);
+
+        if let Some(mut child) = child {
+            if let Err(err) = child.wait() {
+                error!("Recording encoder failed: {err}");
+            }
+        }
+    }
+
+    /// Write all received frames.
+    fn encode(&mut self, receiver: Receiver<Frame>) -> io::Result<()> {
+        let (width, height) = (self.size.w, self.size.h);
+        writeln!(self.writer, "YUV4MPEG2 W{width} H{height} F{}:1000 Ip A1:1 C444", self.refresh)?;
+
+        for frame in receiver {
+            self.repeat_last_frame(frame.time)?;
+
+            self.last_frame = rgba_to_yuv444(&frame.pixels, width * height);
+            self.write_frame()?;
+        }
+
+        // Show the last frame until the recording was stopped.
+        if let Some(stop_time) = self.stop_time.get() {
+            self.repeat_last_frame(*stop_time)?;
+        }
+
+        self.writer.flush()
+    }
+
+    /// Repeat the last frame until the presentation time `time`.
+    ///
+    /// Frames are only rendered after damage, so this fills the time where
+    /// nothing on the screen has changed.
+    fn repeat_last_frame(&mut self, time: Instant) -> io::Result<()> {
+        let start = *self.start.get_or_insert(time);
+        let elapsed = time.saturating_duration_since(start).as_secs_f64();
+        let target_count = (elapsed * self.refresh as f64 / 1000.).round() as u64;
+        while self.frame_count < target_count && !self.last_frame.is_empty() {
+            self.write_frame()?;
+        }
+
+        Ok(())
+    }
+
+    /// Write the last YUV frame.
+    fn write_frame(&mut self) -> io::Result<()> {
+        self.writer.write_all(b"FRAME\n")?;
+        self.writer.write_all(&self.last_frame)?;
+        self.frame_count += 1;
+
+        Ok(())
+    }
+}
+
+/// Convert RGBA pixels to planar full-range BT.601 YUV.
+fn rgba_to_yuv444(rgba: &[u8], pixel_count: i32) -> Vec<u8> {
+    let pixel_count = pixel_count as usize;
+    let mut yuv = vec![0; pixel_count * 3];
+    let (y_plane, uv_planes) = yuv.split_at_mut(pixel_count);
+    let (u_plane, v_plane) = uv_planes.split_at_mut(pixel_count);
+
+    for (i, pixel) in rgba.chunks_exact(4).take(pixel_count).enumerate() {
+        let (r, g, b) = (pixel[0] as i32, pixel[1] as i32, pixel[2] as i32);
+        y_plane[i] = ((77 * r + 150 * g + 29 * b + 128) >> 8).clamp(0, 255) as u8;
+        u_plane[i] = (((-43 * r - 85 * g + 128 * b + 128) >> 8) + 128).clamp(0, 255) as u8;
+        v_plane[i] = (((128 * r - 107 * g - 21 * b + 128) >> 8) + 128).clamp(0, 255) as u8;
+    }
+
+    yuv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yuv_conversion() {
+        let rgba = [255, 255, 255, 255, 255, 0, 0, 255, 0, 0, 255, 0];
+        let yuv = rgba_to_yuv444(&rgba, 3);
+        assert_eq!(yuv, [255, 77, 29, 128, 85, 255, 128, 255, 107]);
+    }
+}
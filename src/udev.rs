@@ -2,11 +2,14 @@
 
 use std::collections::HashMap;
 use std::error::Error;
+use std::fs::File;
 use std::path::{Path, PathBuf};
-use std::time::Duration;
-use std::{env, io, mem, process, ptr};
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use std::{env, io, mem, process, slice};
 
 use _linux_dmabuf::zv1::server::zwp_linux_dmabuf_feedback_v1::TrancheFlags;
+use catacomb_ipc::{AccelProfile, CliToggle, InputCapability, InputDeviceInfo, InputSettings};
 use indexmap::IndexSet;
 use libc::dev_t as DeviceId;
 #[cfg(feature = "profiling")]
@@ -22,12 +25,14 @@ use smithay::backend::drm::gbm::GbmFramebuffer;
 use smithay::backend::drm::{DrmDevice, DrmDeviceFd, DrmEvent, DrmNode, DrmSurface};
 use smithay::backend::egl::context::EGLContext;
 use smithay::backend::egl::display::EGLDisplay;
+use smithay::backend::input::{Event, InputEvent};
 use smithay::backend::libinput::{LibinputInputBackend, LibinputSessionInterface};
 use smithay::backend::renderer::element::RenderElementStates;
-use smithay::backend::renderer::gles::{GlesRenderbuffer, GlesRenderer, ffi};
+use smithay::backend::renderer::gles::{GlesMapping, GlesRenderbuffer, GlesRenderer, ffi};
 use smithay::backend::renderer::sync::SyncPoint;
 use smithay::backend::renderer::{
-    self, Bind, BufferType, Frame, ImportDma, ImportEgl, Offscreen, Renderer, utils,
+    self, Bind, BufferType, ExportMem, Frame, ImportDma, ImportEgl, Offscreen, Renderer,
+    TextureMapping, utils,
 };
 use smithay::backend::session::libseat::LibSeatSession;
 use smithay::backend::session::{AsErrno, Event as SessionEvent, Session};
@@ -43,7 +48,10 @@ use smithay::reexports::drm::control::property::{
     Handle as PropertyHandle, Value as PropertyValue,
 };
 use smithay::reexports::drm::control::{Device, Mode as DrmMode, ModeTypeFlags, ResourceHandles};
-use smithay::reexports::input::Libinput;
+use smithay::reexports::input::{
+    AccelProfile as LibinputAccelProfile, Device as InputDevice, DeviceCapability, Libinput,
+    SendEventsMode,
+};
 use smithay::reexports::rustix::fs::OFlags;
 use smithay::reexports::wayland_protocols::wp::linux_dmabuf as _linux_dmabuf;
 use smithay::reexports::wayland_server::DisplayHandle;
@@ -52,17 +60,16 @@ use smithay::reexports::wayland_server::protocol::wl_shm;
 use smithay::utils::{DevPath, DeviceFd, Logical, Physical, Point, Rectangle, Size, Transform};
 use smithay::wayland::dmabuf::{DmabufFeedback, DmabufFeedbackBuilder};
 use smithay::wayland::{dmabuf, shm};
-use tracing::{debug, error};
+use tracing::{debug, error, warn};
 
 use crate::catacomb::Catacomb;
-use crate::drawing::{CatacombElement, Graphics};
+use crate::drawing::{CatacombElement, Graphics, Theme};
+use crate::geometry::Matrix3x3;
 use crate::output::Output;
 use crate::protocols::screencopy::frame::Screencopy;
-use crate::trace_error;
+use crate::recording::{Recording, RecordingTarget};
 use crate::windows::Windows;
-
-/// Default background color.
-const CLEAR_COLOR: [f32; 4] = [0., 0., 0., 1.];
+use crate::{ipc_server, trace_error};
 
 /// Retry delay after a WouldBlock when trying to add a DRM device.
 const DRM_RETRY_DELAY: Duration = Duration::from_millis(250);
@@ -122,6 +129,7 @@ pub fn run() {
             error!("Event loop error: {error}");
             break;
         }
+        ipc_server::dispatch_events(&mut catacomb);
         catacomb.display_handle.flush_clients().expect("flushing clients");
     }
 }
@@ -141,6 +149,13 @@ fn add_device(catacomb: &mut Catacomb, path: PathBuf) {
 
 /// Udev backend shared state.
 pub struct Udev {
+    /// Libinput settings by device name.
+    input_settings: Vec<(String, InputSettings)>,
+    input_devices: Vec<InputDevice>,
+
+    /// Compositor colors.
+    theme: Theme,
+
     scheduled_redraws: Vec<RegistrationToken>,
     event_loop: LoopHandle<'static, Catacomb>,
     output_device: Option<OutputDevice>,
@@ -169,7 +184,33 @@ impl Udev {
 
         let input_backend = LibinputInputBackend::new(context.clone());
         event_loop
-            .insert_source(input_backend, |event, _, catacomb| catacomb.handle_input(event))
+            .insert_source(input_backend, |event, _, catacomb| {
+                // Track devices for libinput configuration.
+                match &event {
+                    InputEvent::DeviceAdded { device } => {
+                        catacomb.backend.add_input_device(device.clone())
+                    },
+                    InputEvent::DeviceRemoved { device } => {
+                        catacomb.backend.input_devices.retain(|input_device| input_device != device)
+                    },
+                    _ => (),
+                }
+
+                // Drop touch input from ignored devices.
+                let touch_device = match &event {
+                    InputEvent::TouchDown { event } => Some(event.device()),
+                    InputEvent::TouchUp { event } => Some(event.device()),
+                    InputEvent::TouchMotion { event } => Some(event.device()),
+                    InputEvent::TouchCancel { event } => Some(event.device()),
+                    InputEvent::TouchFrame { event } => Some(event.device()),
+                    _ => None,
+                };
+                if touch_device.is_some_and(|device| catacomb.backend.touch_ignored(&device)) {
+                    return;
+                }
+
+                catacomb.handle_input(event)
+            })
             .expect("insert input source");
 
         // Register notifier for handling session events.
@@ -211,7 +252,10 @@ impl Udev {
             event_loop,
             session,
             scheduled_redraws: Default::default(),
+            input_settings: Default::default(),
             output_device: Default::default(),
+            input_devices: Default::default(),
+            theme: Default::default(),
         }
     }
 
@@ -262,6 +306,60 @@ impl Udev {
         }
     }
 
+    /// Save a region of the output as PNG file.
+    pub fn screenshot(
+        &mut self,
+        windows: &mut Windows,
+        cursor_position: Option<Point<f64, Logical>>,
+        region: Option<Rectangle<i32, Logical>>,
+        path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        let output_device = self.output_device.as_mut().ok_or("no output device")?;
+        output_device.screenshot(windows, cursor_position, region, path)?;
+
+        // Ensure global damage consumed by the screenshot is not lost.
+        windows.set_dirty();
+
+        Ok(())
+    }
+
+    /// Start recording all rendered frames.
+    pub fn start_recording(
+        &mut self,
+        windows: &Windows,
+        target: RecordingTarget,
+    ) -> Result<(), Box<dyn Error>> {
+        let output_device = self.output_device.as_mut().ok_or("no output device")?;
+        if output_device.recording.is_some() {
+            return Err("recording is already active".into());
+        }
+
+        let canvas = windows.canvas();
+        let recording = Recording::new(target, canvas.physical_size(), canvas.mode().refresh)?;
+        let recorder = Recorder::new(&mut output_device.gles, recording)?;
+        output_device.recording = Some(recorder);
+
+        Ok(())
+    }
+
+    /// Stop the active screen recording.
+    ///
+    /// Returns `false` if no recording was active.
+    pub fn stop_recording(&mut self) -> bool {
+        let output_device = match &mut self.output_device {
+            Some(output_device) => output_device,
+            None => return false,
+        };
+
+        match output_device.recording.take() {
+            Some(recorder) => {
+                recorder.finish(&mut output_device.gles);
+                true
+            },
+            None => false,
+        }
+    }
+
     /// Get the current output's renderer.
     pub fn renderer(&mut self) -> Option<&mut GlesRenderer> {
         self.output_device.as_mut().map(|output_device| &mut output_device.gles)
@@ -293,6 +391,116 @@ impl Udev {
         }
     }
 
+    /// Get all modes supported by the active output.
+    pub fn output_modes(&self) -> Option<Vec<Mode>> {
+        let output_device = self.output_device.as_ref()?;
+        let mut modes: Vec<_> = output_device.modes().into_iter().map(wayland_mode).collect();
+        modes.dedup();
+        Some(modes)
+    }
+
+    /// Switch the active output to a different mode.
+    pub fn set_mode(&mut self, windows: &mut Windows, mode: Mode) -> Result<(), Box<dyn Error>> {
+        let output_device = self.output_device.as_mut().ok_or("missing output device")?;
+
+        // Find the DRM mode matching the requested mode.
+        let drm_mode = output_device
+            .modes()
+            .into_iter()
+            .find(|drm_mode| wayland_mode(*drm_mode) == mode)
+            .ok_or("unsupported output mode")?;
+
+        output_device.drm_compositor.use_mode(drm_mode)?;
+        windows.set_output_mode(mode);
+
+        Ok(())
+    }
+
+    /// Get the compositor colors.
+    pub fn theme(&self) -> Theme {
+        self.theme
+    }
+
+    /// Change the compositor colors.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+
+        if let Some(output_device) = &mut self.output_device {
+            output_device.graphics.set_theme(theme);
+        }
+    }
+
+    /// Get all connected input devices.
+    pub fn input_devices(&self) -> Vec<InputDeviceInfo> {
+        self.input_devices
+            .iter()
+            .map(|device| {
+                let mut info = input_device_info(device);
+
+                // Touch filtering is handled by Catacomb instead of libinput.
+                if info.capabilities.contains(&InputCapability::Touch) {
+                    let touch =
+                        if self.touch_ignored(device) { CliToggle::Off } else { CliToggle::On };
+                    info.settings.touch = Some(touch);
+                }
+
+                info
+            })
+            .collect()
+    }
+
+    /// Check if touch input from a device should be ignored.
+    fn touch_ignored(&self, device: &InputDevice) -> bool {
+        self.input_settings
+            .iter()
+            .any(|(name, settings)| name == device.name() && settings.touch == Some(CliToggle::Off))
+    }
+
+    /// Update libinput settings for all devices with a matching name.
+    ///
+    /// Settings are stored for devices connected in the future, an error is
+    /// returned if a connected device rejects any of them.
+    pub fn set_input_settings(
+        &mut self,
+        name: String,
+        settings: InputSettings,
+    ) -> Result<(), String> {
+        // Apply settings to connected devices.
+        let mut result = Ok(());
+        for device in self.input_devices.iter_mut().filter(|device| device.name() == name) {
+            result = result.and(apply_input_settings(device, &settings));
+        }
+
+        // Store settings for devices connected in the future.
+        match self.input_settings.iter_mut().find(|(device, _)| device == &name) {
+            Some((_, stored_settings)) => stored_settings.update(&settings),
+            None => self.input_settings.push((name, settings)),
+        }
+
+        result
+    }
+
+    /// Reset all changed device settings to their libinput defaults.
+    pub fn reset_input_settings(&mut self) {
+        for (name, settings) in self.input_settings.drain(..) {
+            for device in self.input_devices.iter_mut().filter(|device| device.name() == name) {
+                reset_input_settings(device, &settings);
+            }
+        }
+    }
+
+    /// Configure and track a new input device.
+    fn add_input_device(&mut self, mut device: InputDevice) {
+        let settings = self.input_settings.iter().find(|(name, _)| name == device.name());
+        if let Some((_, settings)) = settings {
+            if let Err(err) = apply_input_settings(&mut device, settings) {
+                error!("{err}");
+            }
+        }
+
+        self.input_devices.push(device);
+    }
+
     /// Stage a screencopy request for the next frame.
     pub fn request_screencopy(&mut self, screencopy: Screencopy) {
         let output_device = match &mut self.output_device {
@@ -403,7 +611,7 @@ impl Udev {
         let token = self.event_loop.register_dispatcher(dispatcher.clone())?;
 
         // Create OpenGL textures.
-        let graphics = Graphics::new();
+        let graphics = Graphics::new(self.theme);
 
         // Initialize last render state as empty.
         let last_render_states = RenderElementStates { states: HashMap::new() };
@@ -418,6 +626,7 @@ impl Udev {
             drm,
             id: device_id,
             screencopy: Default::default(),
+            recording: Default::default(),
         });
 
         Ok(())
@@ -477,13 +686,8 @@ impl Udev {
         let gbm_flags = GbmBufferFlags::RENDERING | GbmBufferFlags::SCANOUT;
         let allocator = GbmAllocator::new(gbm.clone(), gbm_flags);
 
-        let (width, height) = connector_mode.size();
-        let mode = Mode {
-            size: (width as i32, height as i32).into(),
-            refresh: connector_mode.vrefresh() as i32 * 1000,
-        };
-
         // Update the output mode.
+        let mode = wayland_mode(*connector_mode);
 
         let (physical_width, physical_height) = connector.size().unwrap_or((0, 0));
         let output_name = format!("{:?}", connector.interface());
@@ -549,6 +753,7 @@ impl Udev {
 pub struct OutputDevice {
     last_render_states: RenderElementStates,
     screencopy: Option<Screencopy>,
+    recording: Option<Recorder>,
     drm_compositor: DrmCompositor,
     gbm: GbmDevice<DrmDeviceFd>,
     gles: GlesRenderer,
@@ -584,6 +789,16 @@ impl OutputDevice {
         Err(io::Error::new(io::ErrorKind::NotFound, "missing drm property"))
     }
 
+    /// Get all modes supported by the output's connectors.
+    fn modes(&self) -> Vec<DrmMode> {
+        let connectors = self.drm_compositor.surface().current_connectors();
+        connectors
+            .into_iter()
+            .filter_map(|connector| self.drm.get_connector(connector, false).ok())
+            .flat_map(|connector| connector.modes().to_vec())
+            .collect()
+    }
+
     /// Set output DPMS state.
     fn set_enabled(&mut self, enabled: bool) {
         let property = match self.get_drm_property("ACTIVE") {
@@ -615,11 +830,12 @@ impl OutputDevice {
         // Update output mode since we're using static for transforms.
         self.drm_compositor.set_output_mode_source(windows.canvas().into());
 
+        let clear_color = self.graphics.theme().clear_color();
         let textures = windows.textures(&mut self.gles, &mut self.graphics, cursor_position);
         let mut frame_result = self.drm_compositor.render_frame(
             &mut self.gles,
             textures,
-            CLEAR_COLOR,
+            clear_color,
             FrameFlags::DEFAULT | FrameFlags::ALLOW_PRIMARY_PLANE_SCANOUT_ANY,
         )?;
         let rendered = !frame_result.is_empty;
@@ -668,6 +884,12 @@ impl OutputDevice {
             }
         }
 
+        // Copy frame for screen recording, frames without damage are repeated by the
+        // encoder.
+        if rendered {
+            Self::record_frame(&mut self.gles, &mut self.recording, windows, &frame_result);
+        }
+
         // Skip frame submission if everything used direct scanout.
         if rendered {
             self.drm_compositor.queue_frame(())?;
@@ -712,34 +934,10 @@ impl OutputDevice {
         region: Rectangle<i32, Physical>,
         buffer: &WlBuffer,
     ) -> Result<SyncPoint, Box<dyn Error>> {
-        // Create and bind an offscreen render buffer.
-        let buffer_dimensions = renderer::buffer_dimensions(buffer).unwrap();
-        let mut offscreen_buffer: GlesRenderbuffer =
-            self.gles.create_buffer(Fourcc::Abgr8888, buffer_dimensions)?;
-        let mut framebuffer = self.gles.bind(&mut offscreen_buffer)?;
-
         let canvas = windows.canvas();
-        let scale = canvas.scale();
         let output_size = canvas.physical_resolution();
         let transform = canvas.orientation().output_transform();
 
-        // Calculate drawing area after output transform.
-        let damage = transform.transform_rect_in(region, &output_size);
-
-        // Collect textures for rendering.
-        let textures = windows.textures(&mut self.gles, &mut self.graphics, cursor_position);
-
-        // Initialize the buffer to our clear color.
-        let mut frame = self.gles.render(&mut framebuffer, output_size, transform)?;
-        frame.clear(CLEAR_COLOR.into(), &[damage])?;
-
-        // Render everything to the offscreen buffer.
-        utils::draw_render_elements(&mut frame, scale, textures, &[damage])?;
-
-        // Ensure rendering was fully completed.
-        let sync_point = frame.finish()?;
-
-        // Copy offscreen buffer's content to the SHM buffer.
         shm::with_buffer_contents_mut(buffer, |shm_buffer, shm_len, buffer_data| {
             // Ensure SHM buffer is in an acceptable format.
             if buffer_data.format != wl_shm::Format::Argb8888
@@ -750,32 +948,151 @@ impl OutputDevice {
                 return Err::<_, Box<dyn Error>>("Invalid buffer format".into());
             }
 
-            // Copy framebuffer data to the SHM buffer.
-            self.gles.with_context(|gl| unsafe {
-                gl.ReadPixels(
-                    region.loc.x,
-                    region.loc.y,
-                    region.size.w,
-                    region.size.h,
-                    ffi::RGBA,
-                    ffi::UNSIGNED_BYTE,
-                    shm_buffer.cast(),
-                );
-            })?;
+            // Render framebuffer data to the SHM buffer.
+            let pixels = unsafe { slice::from_raw_parts_mut(shm_buffer, shm_len) };
+            let sync_point = self.render_offscreen(
+                windows,
+                cursor_position,
+                output_size,
+                transform,
+                region,
+                pixels,
+            )?;
 
             // Convert OpenGL's RGBA to ARGB.
-            for i in 0..(region.size.w * region.size.h) as usize {
-                unsafe {
-                    let src = shm_buffer.offset(i as isize * 4);
-                    let dst = shm_buffer.offset(i as isize * 4 + 2);
-                    ptr::swap(src, dst);
-                }
+            for pixel in pixels.chunks_exact_mut(4) {
+                pixel.swap(0, 2);
             }
 
             Ok(sync_point)
         })?
     }
 
+    /// Copy the current frame into the active screen recording.
+    fn record_frame(
+        gles: &mut GlesRenderer,
+        recording: &mut Option<Recorder>,
+        windows: &Windows,
+        frame_result: &RenderFrameResult<GbmBuffer, GbmFramebuffer, CatacombElement>,
+    ) {
+        let recorder = match recording {
+            Some(recorder) => recorder,
+            None => return,
+        };
+
+        // Stop recording when the frame size changes, since YUV4MPEG2 requires a fixed
+        // size.
+        let canvas = windows.canvas();
+        if canvas.physical_size() != recorder.recording.size() {
+            warn!("Stopping screen recording after output size change");
+            if let Some(recorder) = recording.take() {
+                recorder.finish(gles);
+            }
+            return;
+        }
+
+        if let Err(err) = recorder.record(gles, canvas.scale(), frame_result) {
+            error!("Failed to record frame: {err}");
+            *recording = None;
+        }
+    }
+
+    /// Save a region of the output as PNG file.
+    fn screenshot(
+        &mut self,
+        windows: &mut Windows,
+        cursor_position: Option<Point<f64, Logical>>,
+        region: Option<Rectangle<i32, Logical>>,
+        path: &Path,
+    ) -> Result<(), Box<dyn Error>> {
+        // Capture the output as it is displayed, after applying its transform.
+        let canvas = windows.canvas();
+        let output_size = canvas.physical_size();
+        let output_rect = Rectangle::from_size(output_size);
+        let region = match region {
+            Some(region) => region
+                .to_physical_precise_round(canvas.scale())
+                .intersection(output_rect)
+                .ok_or("region is outside of the output")?,
+            None => output_rect,
+        };
+
+        let mut pixels = vec![0; (region.size.w * region.size.h * 4) as usize];
+        self.render_offscreen(
+            windows,
+            cursor_position,
+            output_size,
+            Transform::Normal,
+            region,
+            &mut pixels,
+        )?;
+
+        // Encode pixels as PNG.
+        let file = io::BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(file, region.size.w as u32, region.size.h as u32);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+
+        Ok(())
+    }
+
+    /// Render a region of the output into an RGBA pixel buffer.
+    fn render_offscreen(
+        &mut self,
+        windows: &mut Windows,
+        cursor_position: Option<Point<f64, Logical>>,
+        output_size: Size<i32, Physical>,
+        transform: Transform,
+        region: Rectangle<i32, Physical>,
+        pixels: &mut [u8],
+    ) -> Result<SyncPoint, Box<dyn Error>> {
+        if pixels.len() < (region.size.w * region.size.h * 4) as usize {
+            return Err("Pixel buffer too small for region".into());
+        }
+
+        // Create and bind an offscreen render buffer.
+        let buffer_size = Size::from((output_size.w, output_size.h));
+        let mut offscreen_buffer: GlesRenderbuffer =
+            self.gles.create_buffer(Fourcc::Abgr8888, buffer_size)?;
+        let mut framebuffer = self.gles.bind(&mut offscreen_buffer)?;
+
+        // Calculate drawing area after output transform.
+        let damage = transform.transform_rect_in(region, &output_size);
+
+        // Collect textures for rendering.
+        let scale = windows.canvas().scale();
+        let textures = windows.textures(&mut self.gles, &mut self.graphics, cursor_position);
+
+        // Initialize the buffer to our clear color.
+        let clear_color = self.graphics.theme().clear_color();
+        let mut frame = self.gles.render(&mut framebuffer, output_size, transform)?;
+        frame.clear(clear_color.into(), &[damage])?;
+
+        // Render everything to the offscreen buffer.
+        utils::draw_render_elements(&mut frame, scale, textures, &[damage])?;
+
+        // Ensure rendering was fully completed.
+        let sync_point = frame.finish()?;
+
+        // Copy offscreen buffer's content to the pixel buffer.
+        self.gles.with_context(|gl| unsafe {
+            gl.ReadPixels(
+                region.loc.x,
+                region.loc.y,
+                region.size.w,
+                region.size.h,
+                ffi::RGBA,
+                ffi::UNSIGNED_BYTE,
+                pixels.as_mut_ptr().cast(),
+            );
+        })?;
+
+        Ok(sync_point)
+    }
+
     /// Default dma surface feedback.
     fn default_dmabuf_feedback(
         &self,
@@ -840,6 +1157,255 @@ impl OutputDevice {
     }
 }
 
+/// Get IPC state of a libinput device.
+fn input_device_info(device: &InputDevice) -> InputDeviceInfo {
+    let toggle = |enabled| if enabled { CliToggle::On } else { CliToggle::Off };
+
+    let capabilities = [
+        (DeviceCapability::Keyboard, InputCapability::Keyboard),
+        (DeviceCapability::Pointer, InputCapability::Pointer),
+        (DeviceCapability::Touch, InputCapability::Touch),
+        (DeviceCapability::TabletTool, InputCapability::TabletTool),
+        (DeviceCapability::TabletPad, InputCapability::TabletPad),
+        (DeviceCapability::Gesture, InputCapability::Gesture),
+        (DeviceCapability::Switch, InputCapability::Switch),
+    ];
+    let capabilities = capabilities
+        .into_iter()
+        .filter(|(capability, _)| device.has_capability(*capability))
+        .map(|(_, capability)| capability)
+        .collect();
+
+    let accel_available = device.config_accel_is_available();
+    let accel_profile = match device.config_accel_profile() {
+        Some(LibinputAccelProfile::Flat) => Some(AccelProfile::Flat),
+        Some(LibinputAccelProfile::Adaptive) => Some(AccelProfile::Adaptive),
+        _ => None,
+    };
+
+    let settings = InputSettings {
+        tap: (device.config_tap_finger_count() > 0).then(|| toggle(device.config_tap_enabled())),
+        natural_scroll: device
+            .config_scroll_has_natural_scroll()
+            .then(|| toggle(device.config_scroll_natural_scroll_enabled())),
+        accel_profile: accel_profile.filter(|_| accel_available),
+        accel_speed: accel_available.then(|| device.config_accel_speed()),
+        left_handed: device
+            .config_left_handed_is_available()
+            .then(|| toggle(device.config_left_handed())),
+        send_events: Some(toggle(device.config_send_events_mode() != SendEventsMode::DISABLED)),
+        calibration: device.config_calibration_has_matrix().then(|| {
+            let m = device.config_calibration_matrix().unwrap_or([1., 0., 0., 0., 1., 0.]);
+            format!("{}, {}, {}; {}, {}, {}; 0, 0, 1", m[0], m[1], m[2], m[3], m[4], m[5])
+        }),
+        touch: None,
+    };
+
+    InputDeviceInfo { name: device.name().into(), capabilities, settings }
+}
+
+/// Apply libinput settings to a device.
+fn apply_input_settings(device: &mut InputDevice, settings: &InputSettings) -> Result<(), String> {
+    let name = device.name().to_owned();
+    let mut errors = Vec::new();
+    let mut check = |setting: &str, result: Result<(), _>| {
+        if let Err(err) = result {
+            errors.push(format!("unable to set {setting} for {name:?}: {err:?}"));
+        }
+    };
+
+    if let Some(tap) = settings.tap {
+        check("tap", device.config_tap_set_enabled(tap == CliToggle::On));
+    }
+
+    if let Some(natural_scroll) = settings.natural_scroll {
+        let enabled = natural_scroll == CliToggle::On;
+        check("natural_scroll", device.config_scroll_set_natural_scroll_enabled(enabled));
+    }
+
+    if let Some(accel_profile) = settings.accel_profile {
+        let profile = match accel_profile {
+            AccelProfile::Flat => LibinputAccelProfile::Flat,
+            AccelProfile::Adaptive => LibinputAccelProfile::Adaptive,
+        };
+        check("accel_profile", device.config_accel_set_profile(profile));
+    }
+
+    if let Some(accel_speed) = settings.accel_speed {
+        check("accel_speed", device.config_accel_set_speed(accel_speed));
+    }
+
+    if let Some(left_handed) = settings.left_handed {
+        check("left_handed", device.config_left_handed_set(left_handed == CliToggle::On));
+    }
+
+    if let Some(send_events) = settings.send_events {
+        let mode = match send_events {
+            CliToggle::On => SendEventsMode::ENABLED,
+            CliToggle::Off => SendEventsMode::DISABLED,
+        };
+        check("send_events", device.config_send_events_set_mode(mode));
+    }
+
+    if let Some(calibration) = &settings.calibration {
+        match calibration_matrix(calibration) {
+            Ok(matrix) => {
+                check("calibration", device.config_calibration_set_matrix(matrix));
+            },
+            Err(err) => errors.push(format!("invalid calibration matrix for {name:?}: {err}")),
+        }
+    }
+
+    if errors.is_empty() { Ok(()) } else { Err(errors.join(", ")) }
+}
+
+/// Screen recording frame capture.
+///
+/// Rendered frames are blitted to an offscreen buffer and read back
+/// asynchronously, each frame is only submitted to the encoder once the next
+/// frame is recorded, to avoid stalling the render loop.
+struct Recorder {
+    recording: Recording,
+    buffer: GlesRenderbuffer,
+    pending_frame: Option<(Instant, GlesMapping)>,
+}
+
+impl Recorder {
+    fn new(gles: &mut GlesRenderer, recording: Recording) -> Result<Self, Box<dyn Error>> {
+        let size = recording.size();
+        let buffer: GlesRenderbuffer =
+            gles.create_buffer(Fourcc::Abgr8888, (size.w, size.h).into())?;
+        Ok(Self { recording, buffer, pending_frame: None })
+    }
+
+    /// Start reading back a rendered frame.
+    fn record(
+        &mut self,
+        gles: &mut GlesRenderer,
+        scale: f64,
+        frame_result: &RenderFrameResult<GbmBuffer, GbmFramebuffer, CatacombElement>,
+    ) -> Result<(), Box<dyn Error>> {
+        // Submit the previous frame, its readback should be complete by now.
+        self.submit_pending(gles)?;
+
+        // Copy the already rendered frame, like screencopy does.
+        let size = self.recording.size();
+        let mut framebuffer = gles.bind(&mut self.buffer)?;
+        let damage = [Rectangle::from_size(size)];
+        frame_result.blit_frame_result(
+            size,
+            Transform::Normal,
+            scale,
+            gles,
+            &mut framebuffer,
+            damage,
+            [],
+        )?;
+
+        // Queue asynchronous transfer of the frame to CPU memory.
+        let region = Rectangle::from_size((size.w, size.h).into());
+        let mapping = gles.copy_framebuffer(&framebuffer, region, Fourcc::Abgr8888)?;
+        self.pending_frame = Some((Instant::now(), mapping));
+
+        Ok(())
+    }
+
+    /// Submit the last frame and stop the recording.
+    fn finish(mut self, gles: &mut GlesRenderer) {
+        if let Err(err) = self.submit_pending(gles) {
+            error!("Failed to record frame: {err}");
+        }
+    }
+
+    /// Send the pending frame to the encoder.
+    fn submit_pending(&mut self, gles: &mut GlesRenderer) -> Result<(), Box<dyn Error>> {
+        let (time, mapping) = match self.pending_frame.take() {
+            Some(pending_frame) => pending_frame,
+            None => return Ok(()),
+        };
+
+        // Ensure rows are ordered from top to bottom.
+        let data = gles.map_texture(&mapping)?;
+        let pixels = if mapping.flipped() {
+            let stride = self.recording.size().w as usize * 4;
+            data.chunks_exact(stride).rev().flatten().copied().collect()
+        } else {
+            data.to_vec()
+        };
+
+        if !self.recording.add_frame(time, pixels) {
+            return Err("encoder stopped".into());
+        }
+
+        Ok(())
+    }
+}
+
+/// Restore libinput defaults for all settings which were changed.
+fn reset_input_settings(device: &mut InputDevice, settings: &InputSettings) {
+    let name = device.name().to_owned();
+    let log_error = |setting: &str, result: Result<(), _>| {
+        if let Err(err) = result {
+            error!("Unable to reset {setting} for {name:?}: {err:?}");
+        }
+    };
+
+    if settings.tap.is_some() {
+        let enabled = device.config_tap_default_enabled();
+        log_error("tap", device.config_tap_set_enabled(enabled));
+    }
+
+    if settings.natural_scroll.is_some() {
+        let enabled = device.config_scroll_default_natural_scroll_enabled();
+        log_error("natural_scroll", device.config_scroll_set_natural_scroll_enabled(enabled));
+    }
+
+    if settings.accel_profile.is_some() {
+        if let Some(profile) = device.config_accel_default_profile() {
+            log_error("accel_profile", device.config_accel_set_profile(profile));
+        }
+    }
+
+    if settings.accel_speed.is_some() {
+        let speed = device.config_accel_default_speed();
+        log_error("accel_speed", device.config_accel_set_speed(speed));
+    }
+
+    if settings.left_handed.is_some() {
+        let enabled = device.config_left_handed_default();
+        log_error("left_handed", device.config_left_handed_set(enabled));
+    }
+
+    if settings.send_events.is_some() {
+        let mode = device.config_send_events_default_mode();
+        log_error("send_events", device.config_send_events_set_mode(mode));
+    }
+
+    if settings.calibration.is_some() {
+        if let Some(matrix) = device.config_calibration_default_matrix() {
+            log_error("calibration", device.config_calibration_set_matrix(matrix));
+        }
+    }
+}
+
+/// Parse a libinput touch calibration matrix.
+pub fn calibration_matrix(matrix: &str) -> Result<[f32; 6], Box<dyn Error>> {
+    let matrix = Matrix3x3::<f32>::from_str(matrix)?;
+
+    // Libinput only supports affine transformations.
+    if !matrix.is_affine() {
+        return Err("last matrix row must be \"0, 0, 1\"".into());
+    }
+
+    Ok(matrix.affine())
+}
+
+/// Convert a DRM mode to a Wayland output mode.
+fn wayland_mode(mode: DrmMode) -> Mode {
+    let (width, height) = mode.size();
+    Mode { size: (width as i32, height as i32).into(), refresh: mode.vrefresh() as i32 * 1000 }
+}
+
 /// DRM compositor type alias.
 type DrmCompositor = SmithayDrmCompositor<
     GbmAllocator<DrmDeviceFd>,
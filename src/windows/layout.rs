@@ -42,6 +42,11 @@ impl Layouts {
         self.layouts.get(index)
     }
 
+    /// Get the index of the currently visible layout.
+    pub fn active_index(&self) -> Option<usize> {
+        self.active_layout
+    }
+
     /// Get the currently visible layout.
     pub fn active(&self) -> &Layout {
         self.active_layout.and_then(|i| self.layouts.get(i)).unwrap_or(&DEFAULT_LAYOUT)
@@ -80,6 +85,11 @@ impl Layouts {
                         }
                     }
                 },
+                Transaction::Swap(index) => {
+                    if self.layouts.get(*index).is_some_and(|swapped| swapped.id == layout.id) {
+                        mem::swap(&mut layout.primary, &mut layout.secondary);
+                    }
+                },
             }
         }
 
@@ -245,6 +255,73 @@ impl Layouts {
         self.add_transaction(Transaction::Secondary(position));
     }
 
+    /// Swap the active layout's primary and secondary window.
+    pub fn swap_active(&mut self, output: &Output) {
+        let index = match self.active_layout {
+            Some(index) => index,
+            None => return,
+        };
+
+        // Ignore layouts without two windows.
+        let layout = &self.layouts[index];
+        let (primary, secondary) = match (&layout.primary, &layout.secondary) {
+            (Some(primary), Some(secondary)) => (primary, secondary),
+            _ => return,
+        };
+
+        // Resize both windows to their new positions.
+        let rectangle = output.secondary_rectangle();
+        primary.borrow_mut().set_dimensions(output.scale(), rectangle);
+        let rectangle = output.primary_rectangle(true);
+        secondary.borrow_mut().set_dimensions(output.scale(), rectangle);
+
+        self.add_transaction(Transaction::Swap(index));
+    }
+
+    /// Move a new window from its own layout into the visible layout.
+    ///
+    /// The window either replaces the visible layout's secondary, or becomes
+    /// its primary with the previous primary moving to the secondary position.
+    pub fn place_new(&mut self, output: &Output, position: LayoutPosition, primary: bool) {
+        let active_index = match self.active_layout {
+            Some(index) if index != position.index => index,
+            _ => return,
+        };
+
+        // Stay on the visible layout, instead of switching to the new window's layout.
+        let transaction_count = self.transactions.len();
+        self.transactions.retain(|transaction| {
+            !matches!(transaction, Transaction::Active(Some(index)) if *index == position.index)
+        });
+        if self.transactions.len() != transaction_count
+            && self.parent_layouts.last() == Some(&self.active().id)
+        {
+            self.parent_layouts.pop();
+        }
+
+        self.set_secondary(output, position);
+
+        if !primary {
+            return;
+        }
+
+        // Resize both windows to their swapped positions.
+        let layout = &self.layouts[active_index];
+        if let Some(old_primary) = &layout.primary {
+            let rectangle = output.secondary_rectangle();
+            old_primary.borrow_mut().set_dimensions(output.scale(), rectangle);
+        }
+        let new_layout = &self.layouts[position.index];
+        let new_window =
+            if position.secondary { &new_layout.secondary } else { &new_layout.primary };
+        if let Some(new_window) = new_window {
+            let rectangle = output.primary_rectangle(true);
+            new_window.borrow_mut().set_dimensions(output.scale(), rectangle);
+        }
+
+        self.add_transaction(Transaction::Swap(active_index));
+    }
+
     /// Stage a dead window for reaping.
     pub fn reap(&self, output: &Output, surface: &ToplevelSurface) {
         // Ensure window is reaped even if no resize is required.
@@ -291,6 +368,7 @@ impl Layouts {
                 Transaction::Secondary(position) => {
                     self.apply_secondary_transaction(output, position);
                 },
+                Transaction::Swap(index) => self.apply_swap_transaction(index),
             }
         }
         self.transactions.clear();
@@ -451,6 +529,19 @@ impl Layouts {
         }
     }
 
+    /// Apply a primary and secondary window swap transaction.
+    fn apply_swap_transaction(&mut self, index: usize) {
+        let layout = match self.layouts.get_mut(index) {
+            Some(layout) => layout,
+            None => return,
+        };
+
+        // Ensure transaction was not invalidated by previous transaction.
+        if layout.primary.is_some() && layout.secondary.is_some() {
+            mem::swap(&mut layout.primary, &mut layout.secondary);
+        }
+    }
+
     /// Add transactional update.
     fn add_transaction(&mut self, transaction: Transaction) {
         windows::start_transaction();
@@ -543,9 +634,12 @@ impl Layouts {
         for window in active_layout.windows() {
             let window_ref = window.borrow();
             if let Some(mut surface) = window_ref.surface_at(output_scale, position) {
-                let app_id = window_ref.app_id.clone();
-                let window = Rc::downgrade(window);
-                surface.toplevel = Some(InputSurfaceKind::Layout((window, app_id)));
+                // Only set new focus target if focus is accepted.
+                if window_ref.accepts_focus() {
+                    let app_id = window_ref.app_id.clone();
+                    let window = Rc::downgrade(window);
+                    surface.toplevel = Some(InputSurfaceKind::Layout((window, app_id)));
+                }
                 return Some(surface);
             }
         }
@@ -638,6 +732,7 @@ enum Transaction {
     Active(Option<usize>),
     Primary(LayoutPosition),
     Secondary(LayoutPosition),
+    Swap(usize),
 }
 
 /// Reference to a specific window in a layout.
@@ -8,10 +8,11 @@ use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::{Duration, Instant, UNIX_EPOCH};
 
 use _decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as DecorationMode;
-use catacomb_ipc::{AppIdMatcher, WindowScale};
+use catacomb_ipc::{AppIdMatcher, LayoutSlot, WindowInfo, WindowRule, WindowScale};
 use smithay::backend::drm::DrmEventMetadata;
 use smithay::backend::renderer::element::{Element, RenderElementStates};
 use smithay::backend::renderer::gles::GlesRenderer;
+use smithay::output::Mode;
 use smithay::reexports::calloop::LoopHandle;
 use smithay::reexports::wayland_protocols::xdg::decoration as _decoration;
 use smithay::reexports::wayland_protocols::xdg::shell::server::xdg_toplevel::State;
@@ -28,7 +29,7 @@ use crate::drawing::{CatacombElement, Graphics};
 use crate::input::{HandleGesture, TouchState};
 use crate::layer::Layers;
 use crate::orientation::Orientation;
-use crate::output::{Canvas, GESTURE_HANDLE_HEIGHT, Output};
+use crate::output::{Canvas, Output};
 use crate::overview::{DragActionType, DragAndDrop, Overview};
 use crate::windows::layout::{LayoutPosition, Layouts};
 use crate::windows::surface::{CatacombLayerSurface, InputSurface, InputSurfaceKind, Surface};
@@ -98,7 +99,7 @@ macro_rules! with_all_windows_mut {
 /// Container tracking all known clients.
 #[derive(Debug)]
 pub struct Windows {
-    pub window_scales: Vec<(AppIdMatcher, WindowScale)>,
+    pub window_rules: Vec<(AppIdMatcher, WindowRule)>,
 
     orphan_popups: Vec<Window<PopupSurface>>,
     layouts: Layouts,
@@ -124,11 +125,20 @@ pub struct Windows {
     unlocked_orientation: Orientation,
     orientation_locked: bool,
 
+    /// Orientation before window rules are applied.
+    base_orientation: Orientation,
+
+    /// Orientation requested by the focused window's rules.
+    forced_orientation: Option<Orientation>,
+
     /// IME force enable/disable state.
     ime_override: Option<bool>,
 
     /// Client-independent damage.
     dirty: bool,
+
+    /// Window state changes not yet reported to IPC subscribers.
+    ipc_dirty: bool,
 }
 
 impl Windows {
@@ -143,9 +153,12 @@ impl Windows {
             start_time: Instant::now(),
             orientation_locked: true,
             dirty: true,
+            ipc_dirty: Default::default(),
             unlocked_orientation: Default::default(),
+            forced_orientation: Default::default(),
+            base_orientation: Default::default(),
             orphan_popups: Default::default(),
-            window_scales: Default::default(),
+            window_rules: Default::default(),
             ime_override: Default::default(),
             transaction: Default::default(),
             activated: Default::default(),
@@ -244,6 +257,11 @@ impl Windows {
         self.start_transaction().view = Some(View::Workspace);
     }
 
+    /// Check if the session is locked.
+    pub fn locked(&self) -> bool {
+        matches!(self.pending_view(), View::Lock(_))
+    }
+
     /// Find the XDG shell window responsible for a specific surface.
     pub fn find_xdg(&mut self, wl_surface: &WlSurface) -> Option<RefMut<'_, Window>> {
         // Get root surface.
@@ -279,7 +297,16 @@ impl Windows {
 
         // Handle XDG surface commits.
         if let Some(mut window) = find_window!(self.layouts.windows_mut()) {
-            window.surface_commit_common(scale, &self.window_scales, surface);
+            let app_id = window.app_id.clone();
+            let initial_commit = window.surface_commit_common(scale, &self.window_rules, surface);
+            self.ipc_dirty |= window.app_id != app_id;
+
+            if initial_commit {
+                let (toplevel, rule) = (window.surface.clone(), *window.rule());
+                drop(window);
+
+                self.apply_window_rule(&toplevel, rule);
+            }
             return;
         }
 
@@ -303,7 +330,7 @@ impl Windows {
         // Handle layer shell surface commits.
         let old_exclusive = *self.output.exclusive();
         let fullscreen_active = matches!(self.view, View::Fullscreen(_));
-        window.surface_commit(&self.window_scales, &mut self.output, fullscreen_active, surface);
+        window.surface_commit(&self.window_rules, &mut self.output, fullscreen_active, surface);
 
         // Resize windows after exclusive zone changes.
         if self.output.exclusive() != &old_exclusive {
@@ -377,7 +404,9 @@ impl Windows {
         self.textures.clear();
 
         // Draw gesture handle when not in fullscreen/lock view.
-        if !matches!(self.view, View::Fullscreen(_) | View::Lock(_)) {
+        if !matches!(self.view, View::Fullscreen(_) | View::Lock(_))
+            && self.canvas.gesture_handle_height() > 0
+        {
             // Get texture for gesture handle.
             let gesture_handle = graphics.gesture_handle(renderer, &self.canvas, self.ime_override);
 
@@ -607,22 +636,24 @@ impl Windows {
         }
 
         let focused = match self.layouts.focus.as_ref().map(Weak::upgrade) {
+            // Ignore windows which deny focus.
+            Some(Some(window)) if !window.borrow().accepts_focus() => None,
             // Use focused surface if the window is still alive.
             Some(Some(window)) => {
                 // Clear urgency.
                 let mut window = window.borrow_mut();
-                window.urgent = false;
+                self.ipc_dirty |= mem::take(&mut window.urgent);
 
                 Some((window.surface.clone(), window.app_id.clone()))
             },
             // Fallback to primary if secondary perished.
             Some(None) => {
                 let active_layout = self.layouts.pending_active();
-                let primary = active_layout.primary();
+                let primary = active_layout.primary().filter(|w| w.borrow().accepts_focus());
                 let focused = primary.map(|window| {
                     // Clear urgency.
                     let mut window = window.borrow_mut();
-                    window.urgent = false;
+                    self.ipc_dirty |= mem::take(&mut window.urgent);
 
                     (window.surface.clone(), window.app_id.clone())
                 });
@@ -710,6 +741,7 @@ impl Windows {
 
         // Clear transaction timer.
         TRANSACTION_START.store(0, Ordering::Relaxed);
+        self.ipc_dirty = true;
 
         // Store old visible window count to see if we need to redraw.
         let old_layout_count = self.layouts.active().window_count();
@@ -838,6 +870,37 @@ impl Windows {
 
     /// Set output orientation, completely bypassing locking logic.
     fn set_orientation(&mut self, orientation: Orientation) {
+        self.base_orientation = orientation;
+        self.apply_orientation();
+    }
+
+    /// Apply orientation rules of the focused window.
+    pub fn update_forced_orientation(&mut self) {
+        // Keep the current orientation while the session is locked.
+        if let View::Lock(_) = self.view {
+            return;
+        }
+
+        let focused_window = self.layouts.focus.as_ref().and_then(Weak::upgrade);
+        let orientation = focused_window.and_then(|window| window.borrow().rule().orientation);
+        self.set_forced_orientation(orientation);
+    }
+
+    /// Override the output orientation using window rules.
+    fn set_forced_orientation(&mut self, orientation: Option<Orientation>) {
+        if self.forced_orientation != orientation {
+            self.forced_orientation = orientation;
+            self.apply_orientation();
+        }
+    }
+
+    /// Update output orientation, giving window rules precedence.
+    fn apply_orientation(&mut self) {
+        let orientation = self.forced_orientation.unwrap_or(self.base_orientation);
+        if orientation == self.output.orientation() {
+            return;
+        }
+
         // Start transaction to ensure output transaction will be applied.
         start_transaction();
 
@@ -906,7 +969,7 @@ impl Windows {
     }
 
     /// Handle start of touch input.
-    pub fn on_touch_start(&mut self, point: Point<f64, Logical>) {
+    pub fn on_touch_start(&mut self, point: Point<f64, Logical>, hold_duration: Duration) {
         let overview = match &mut self.view {
             View::Overview(overview) => overview,
             _ => return,
@@ -914,7 +977,7 @@ impl Windows {
 
         // Hold on overview window stages it for D&D.
         if let Some(position) = overview.layout_position(&self.output, &self.layouts, point) {
-            overview.start_hold(&self.event_loop, position);
+            overview.start_hold(&self.event_loop, position, hold_duration);
         }
 
         overview.drag_action = Default::default();
@@ -928,7 +991,8 @@ impl Windows {
             View::Overview(overview) => overview,
             // Handle IME override toggle on gesture handle tap.
             View::Workspace => {
-                *toggle_ime = point.y >= (self.canvas.size().h - GESTURE_HANDLE_HEIGHT) as f64
+                *toggle_ime = point.y
+                    >= (self.canvas.size().h - self.canvas.gesture_handle_height()) as f64
                     && self.focus().is_none();
                 return;
             },
@@ -958,7 +1022,7 @@ impl Windows {
 
         // Ignore tap outside of gesture handle.
         let canvas_size = self.canvas.size().to_f64();
-        if point.y < (canvas_size.h - GESTURE_HANDLE_HEIGHT as f64) {
+        if point.y < (canvas_size.h - self.canvas.gesture_handle_height() as f64) {
             return;
         }
 
@@ -1147,7 +1211,7 @@ impl Windows {
         macro_rules! focus_layer_surface {
             ($window:expr, $surface:expr) => {{
                 // Only set new focus target if focus is accepted.
-                if !$window.deny_focus {
+                if $window.accepts_focus() {
                     let wl_surface = $window.surface().clone();
                     let app_id = $window.app_id.clone();
                     $surface.toplevel = Some(InputSurfaceKind::Layer((wl_surface, app_id)));
@@ -1170,9 +1234,11 @@ impl Windows {
                 let mut surface = window_ref.surface_at(scale, position)?;
 
                 // Set toplevel to update focus.
-                let app_id = window_ref.app_id.clone();
-                let window = Rc::downgrade(window);
-                surface.toplevel = Some(InputSurfaceKind::Layout((window, app_id)));
+                if window_ref.accepts_focus() {
+                    let app_id = window_ref.app_id.clone();
+                    let window = Rc::downgrade(window);
+                    surface.toplevel = Some(InputSurfaceKind::Layout((window, app_id)));
+                }
 
                 return Some(surface);
             },
@@ -1201,21 +1267,132 @@ impl Windows {
 
     /// Add a per-window scale override.
     pub fn add_window_scale(&mut self, app_id: AppIdMatcher, scale: WindowScale) {
-        // Remove previous scale assignments for this EXACT regex.
-        self.window_scales.retain(|(matcher, _)| matcher.base() != app_id.base());
+        // Only store scale if it is transformative to the output scale.
+        let scale = match scale {
+            WindowScale::Additive(scale) | WindowScale::Subtractive(scale) if scale == 0. => None,
+            WindowScale::Multiplicative(scale) | WindowScale::Divisive(scale) if scale == 1. => {
+                None
+            },
+            scale => Some(scale),
+        };
 
-        // Add scale if it is transformative to the output scale.
-        match scale {
-            WindowScale::Additive(scale) | WindowScale::Subtractive(scale) if scale == 0. => (),
-            WindowScale::Multiplicative(scale) | WindowScale::Divisive(scale) if scale == 1. => (),
-            _ => self.window_scales.push((app_id, scale)),
+        self.update_window_rule(app_id, |rule| rule.scale = scale);
+    }
+
+    /// Remove per-window scale overrides.
+    ///
+    /// This removes the override of the EXACT App ID regex, or all overrides if
+    /// no App ID is supplied.
+    pub fn remove_window_scales(&mut self, app_id: Option<&str>) {
+        for (matcher, rule) in &mut self.window_rules {
+            if app_id.is_none_or(|app_id| matcher.base() == app_id) {
+                rule.scale = None;
+            }
         }
 
-        // Update existing window scales.
-        let window_scales = mem::take(&mut self.window_scales);
+        // Remove rules without any effect.
+        self.window_rules.retain(|(_, rule)| rule != &WindowRule::default());
+
+        self.update_window_rules();
+    }
+
+    /// Replace all window rules except scaling for an App ID.
+    pub fn set_window_rule(&mut self, app_id: AppIdMatcher, new_rule: WindowRule) {
+        self.update_window_rule(app_id, |rule| {
+            *rule = WindowRule { scale: rule.scale, ..new_rule }
+        });
+    }
+
+    /// Remove all window rules.
+    pub fn clear_window_rules(&mut self) {
+        self.window_rules.clear();
+        self.update_window_rules();
+    }
+
+    /// Modify the window rule for an EXACT App ID regex.
+    fn update_window_rule(&mut self, app_id: AppIdMatcher, update: impl FnOnce(&mut WindowRule)) {
+        let index =
+            self.window_rules.iter().position(|(matcher, _)| matcher.base() == app_id.base());
+        let index = index.unwrap_or_else(|| {
+            self.window_rules.push((app_id, WindowRule::default()));
+            self.window_rules.len() - 1
+        });
+        update(&mut self.window_rules[index].1);
+
+        // Remove rules without any effect.
+        self.window_rules.retain(|(_, rule)| rule != &WindowRule::default());
+
+        self.update_window_rules();
+    }
+
+    /// Update existing windows' rules.
+    fn update_window_rules(&mut self) {
+        let window_rules = mem::take(&mut self.window_rules);
         let output_scale = self.output.scale();
-        with_all_windows_mut!(self, |window| window.set_window_scale(&window_scales, output_scale));
-        self.window_scales = window_scales;
+        with_all_windows_mut!(self, |window| window.set_window_rules(&window_rules, output_scale));
+        self.window_rules = window_rules;
+
+        self.update_forced_orientation();
+    }
+
+    /// Apply placement and fullscreen rules to a new toplevel.
+    fn apply_window_rule(&mut self, surface: &ToplevelSurface, rule: WindowRule) {
+        // Open new windows in the visible layout.
+        let position = self.layouts.position(surface.surface());
+        if let (Some(placement), Some(position)) = (rule.placement, position) {
+            let primary = placement == LayoutSlot::Primary;
+            self.layouts.place_new(&self.output, position, primary);
+        }
+
+        if rule.fullscreen == Some(true) {
+            self.fullscreen(surface);
+        }
+    }
+
+    /// Get IPC state of all toplevel windows.
+    pub fn window_info(&self) -> Vec<WindowInfo> {
+        let output_scale = self.output.scale();
+        let focus = self.layouts.focus.as_ref().and_then(Weak::upgrade);
+        let fullscreen = match self.pending_view() {
+            View::Fullscreen(window) => Some(window),
+            _ => None,
+        };
+
+        let mut windows = Vec::new();
+        for (index, layout) in self.layouts.layouts().iter().enumerate() {
+            let slots = [
+                (layout.primary(), LayoutSlot::Primary),
+                (layout.secondary(), LayoutSlot::Secondary),
+            ];
+            for (window, position) in slots.into_iter().filter_map(|(w, p)| Some((w?, p))) {
+                let is_window = |other: Option<&Rc<RefCell<Window>>>| {
+                    other.is_some_and(|other| Rc::ptr_eq(other, window))
+                };
+                let focused = is_window(focus.as_ref());
+                let fullscreen = is_window(fullscreen);
+
+                let window = window.borrow();
+                windows.push(WindowInfo {
+                    id: window.id(),
+                    app_id: window.app_id.clone(),
+                    title: window.title(),
+                    layout: index,
+                    position,
+                    focused,
+                    urgent: window.urgent,
+                    fullscreen,
+                    scale: window.window_scale(output_scale),
+                    rectangle: window.bounds(output_scale).into(),
+                });
+            }
+        }
+
+        windows
+    }
+
+    /// Get the index of the currently visible layout.
+    pub fn active_layout(&self) -> Option<usize> {
+        self.layouts.active_index()
     }
 
     /// Check if a surface is currently visible.
@@ -1277,6 +1454,25 @@ impl Windows {
         self.output = output;
     }
 
+    /// Get the active output.
+    pub fn output(&self) -> &Output {
+        &self.output
+    }
+
+    /// Update the output's active mode.
+    pub fn set_output_mode(&mut self, mode: Mode) {
+        self.start_transaction();
+        self.output.set_mode(mode);
+        self.resize_all();
+    }
+
+    /// Update the height reserved for the gesture handle.
+    pub fn set_gesture_handle_height(&mut self, height: i32) {
+        self.start_transaction();
+        self.output.set_gesture_handle_height(height);
+        self.resize_all();
+    }
+
     /// Get access to the current canvas.
     ///
     /// This is different from [`Self::output`] by returning a cached output
@@ -1301,6 +1497,11 @@ impl Windows {
         self.dirty = true;
     }
 
+    /// Check for window state changes since the last call.
+    pub fn take_ipc_dirty(&mut self) -> bool {
+        mem::take(&mut self.ipc_dirty)
+    }
+
     /// Raise a surface's window to the foreground.
     pub fn raise(&mut self, surface: &WlSurface) {
         if let Some(layout_position) = self.layouts.position(surface) {
@@ -1308,10 +1509,117 @@ impl Windows {
         }
     }
 
+    /// Find the first toplevel matching an App ID and window ID.
+    pub fn find_toplevel(
+        &self,
+        app_id: Option<&AppIdMatcher>,
+        id: Option<u64>,
+    ) -> Option<ToplevelSurface> {
+        self.layouts
+            .windows()
+            .filter(|window| app_id.is_none_or(|app_id| app_id.matches(window.app_id.as_ref())))
+            .find(|window| id.is_none_or(|id| window.id() == id))
+            .map(|window| window.surface.clone())
+    }
+
+    /// Switch to a toplevel's layout and focus it.
+    pub fn focus_toplevel(&mut self, surface: &ToplevelSurface) {
+        // Leave fullscreen unless the toplevel itself is fullscreened.
+        if !self.is_fullscreen(surface) {
+            self.unfullscreen(None);
+        }
+
+        self.raise(surface.surface());
+        self.close_overview();
+    }
+
+    /// Show a toplevel as the active layout's primary window.
+    pub fn set_primary(&mut self, surface: &ToplevelSurface) {
+        if let Some(position) = self.layouts.position(surface.surface()) {
+            self.unfullscreen(None);
+            self.layouts.set_primary(&self.output, position);
+            self.close_overview();
+        }
+    }
+
+    /// Show a toplevel as the active layout's secondary window.
+    pub fn set_secondary(&mut self, surface: &ToplevelSurface) {
+        if let Some(position) = self.layouts.position(surface.surface()) {
+            self.unfullscreen(None);
+            self.layouts.set_secondary(&self.output, position);
+            self.close_overview();
+        }
+    }
+
+    /// Switch to the layout at the specified index.
+    ///
+    /// Returns `false` if there is no layout at this index.
+    pub fn set_active_layout(&mut self, index: usize) -> bool {
+        if index >= self.layouts.len() {
+            return false;
+        }
+
+        self.unfullscreen(None);
+        self.layouts.set_active(&self.output, Some(LayoutPosition::new(index, false)), true);
+        self.close_overview();
+
+        true
+    }
+
+    /// Switch to the layout `n` layouts away from the active one.
+    pub fn cycle_active_layout(&mut self, n: isize) {
+        self.unfullscreen(None);
+        self.layouts.cycle_active(&self.output, n);
+        self.close_overview();
+    }
+
+    /// Swap the active layout's primary and secondary window.
+    pub fn swap_active_layout(&mut self) {
+        self.unfullscreen(None);
+        self.layouts.swap_active(&self.output);
+        self.close_overview();
+    }
+
+    /// Return to the workspace view if the overview is open.
+    fn close_overview(&mut self) {
+        if let View::Overview(_) | View::DragAndDrop(_) = self.pending_view() {
+            self.set_view(View::Workspace);
+        }
+    }
+
+    /// Toggle fullscreen for a toplevel.
+    pub fn toggle_fullscreen(&mut self, surface: &ToplevelSurface) {
+        if self.is_fullscreen(surface) {
+            self.unfullscreen(surface);
+        } else {
+            self.focus_toplevel(surface);
+            self.fullscreen(surface);
+        }
+    }
+
+    /// Close a toplevel and remove it without waiting for the client.
+    pub fn kill(&mut self, surface: &ToplevelSurface) {
+        self.unfullscreen(surface);
+
+        if let Some(window) = self.layouts.find_window(surface.surface()) {
+            window.borrow_mut().kill();
+            self.layouts.reap(&self.output, surface);
+        }
+    }
+
+    /// Check if a toplevel is currently fullscreened.
+    fn is_fullscreen(&self, surface: &ToplevelSurface) -> bool {
+        match self.pending_view() {
+            View::Fullscreen(window) => window.borrow().surface == *surface,
+            _ => false,
+        }
+    }
+
     /// Mark a surface's window as urgent.
     pub fn set_urgent(&mut self, surface: &WlSurface, urgent: bool) {
         if let Some(window) = self.layouts.find_window(surface) {
             window.borrow_mut().urgent = urgent;
+            self.ipc_dirty = true;
         }
     }
 
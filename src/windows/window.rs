@@ -3,11 +3,12 @@
 use std::cell::RefCell;
 use std::ops::{Deref, DerefMut};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Instant;
 use std::{cmp, mem};
 
 use _presentation_time::wp_presentation_feedback::Kind as FeedbackKind;
-use catacomb_ipc::{AppIdMatcher, WindowScale};
+use catacomb_ipc::{AppIdMatcher, WindowRule, WindowScale};
 use smithay::backend::drm::{DrmEventMetadata, DrmEventTime};
 use smithay::backend::renderer::element::{RenderElementPresentationState, RenderElementStates};
 use smithay::backend::renderer::gles::GlesRenderer;
@@ -41,6 +42,9 @@ use crate::output::{ExclusiveSpace, Output};
 use crate::windows;
 use crate::windows::surface::{CatacombLayerSurface, InputSurface, Surface};
 
+/// Counter for window IDs.
+static NEXT_WINDOW_ID: AtomicU64 = AtomicU64::new(1);
+
 /// Wayland client window state.
 #[derive(Debug)]
 pub struct Window<S = ToplevelSurface> {
@@ -86,8 +90,14 @@ pub struct Window<S = ToplevelSurface> {
     /// Window-specific scale override.
     scale: Option<WindowScale>,
 
+    /// Window rules matching the App ID.
+    rule: WindowRule,
+
     /// Window liveliness override.
     dead: bool,
+
+    /// Unique window identifier.
+    id: u64,
 }
 
 impl<S: Surface + 'static> Window<S> {
@@ -112,8 +122,10 @@ impl<S: Surface + 'static> Window<S> {
             urgent: Default::default(),
             dirty: Default::default(),
             scale: Default::default(),
+            rule: Default::default(),
             size: Default::default(),
             dead: Default::default(),
+            id: NEXT_WINDOW_ID.fetch_add(1, Ordering::Relaxed),
         };
 
         // Ensure preferred integer scale and per-window layer shell scale are set.
@@ -462,12 +474,14 @@ impl<S: Surface + 'static> Window<S> {
     }
 
     /// Handle common surface commit logic for surfaces of any kind.
+    ///
+    /// Returns `true` for the surface's initial commit.
     pub fn surface_commit_common(
         &mut self,
         output_scale: f64,
-        window_scales: &[(AppIdMatcher, WindowScale)],
+        window_rules: &[(AppIdMatcher, WindowRule)],
         surface: &WlSurface,
-    ) {
+    ) -> bool {
         // Cancel transactions on the commit after the configure was acked.
         self.acked_size = self.surface.acked_size();
 
@@ -510,6 +524,7 @@ impl<S: Surface + 'static> Window<S> {
         );
 
         // Send initial configure after the first commit.
+        let initial_commit = !self.surface.initial_configure_sent();
         self.surface.initial_configure();
 
         // Update the App ID.
@@ -530,10 +545,12 @@ impl<S: Surface + 'static> Window<S> {
             }
         });
 
-        // Try to update window scale when App ID changes.
+        // Try to update window rules when App ID changes.
         if app_id_changed {
-            self.set_window_scale(window_scales, output_scale);
+            self.set_window_rules(window_rules, output_scale);
         }
+
+        initial_commit
     }
 
     /// Update the window's surface transforms.
@@ -549,16 +566,20 @@ impl<S: Surface + 'static> Window<S> {
         }
     }
 
-    /// Update the pre-window scale override.
-    pub fn set_window_scale(
+    /// Update the window rules and per-window scale override.
+    pub fn set_window_rules(
         &mut self,
-        window_scales: &[(AppIdMatcher, WindowScale)],
+        window_rules: &[(AppIdMatcher, WindowRule)],
         output_scale: f64,
     ) {
+        // Combine all matching rules, with earlier rules taking precedence.
+        self.rule = window_rules
+            .iter()
+            .filter(|(matcher, _)| matcher.matches(self.app_id.as_ref()))
+            .fold(WindowRule::default(), |rule, (_, other)| rule.merge(other));
+
         // Update per-window scale, handling identical scales as no-op.
-        let window_scale = window_scales.iter().find_map(|(matcher, window_scale)| {
-            matcher.matches(self.app_id.as_ref()).then_some(*window_scale)
-        });
+        let window_scale = self.rule.scale;
         if window_scale == self.scale {
             return;
         }
@@ -617,7 +638,7 @@ impl<S: Surface + 'static> Window<S> {
     }
 
     /// Get per-window scale for the specified output scale.
-    fn window_scale(&self, output_scale: f64) -> f64 {
+    pub fn window_scale(&self, output_scale: f64) -> f64 {
         self.scale.map_or(output_scale, |scale| scale.scale(output_scale))
     }
 
@@ -833,6 +854,21 @@ impl<S: Surface + 'static> Window<S> {
     pub fn surface(&self) -> &WlSurface {
         self.surface.surface()
     }
+
+    /// Get the unique window identifier.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Get the window rules matching the App ID.
+    pub fn rule(&self) -> &WindowRule {
+        &self.rule
+    }
+
+    /// Check if the window accepts keyboard focus.
+    pub fn accepts_focus(&self) -> bool {
+        !self.deny_focus && self.rule.deny_focus != Some(true)
+    }
 }
 
 impl Window {
@@ -841,6 +877,14 @@ impl Window {
         self.surface.send_close();
         self.dead = true;
     }
+
+    /// Get the window's title.
+    pub fn title(&self) -> Option<String> {
+        compositor::with_states(self.surface(), |states| {
+            let attributes = states.data_map.get::<Mutex<XdgToplevelSurfaceRoleAttributes>>()?;
+            attributes.lock().ok()?.title.clone()
+        })
+    }
 }
 
 impl Window<PopupSurface> {
@@ -867,14 +911,14 @@ impl Window<CatacombLayerSurface> {
     /// Handle a surface commit for layer shell windows.
     pub fn surface_commit(
         &mut self,
-        window_scales: &[(AppIdMatcher, WindowScale)],
+        window_rules: &[(AppIdMatcher, WindowRule)],
         output: &mut Output,
         fullscreen_active: bool,
         surface: &WlSurface,
     ) {
         self.update_layer_state(output);
         self.update_dimensions(output, fullscreen_active);
-        self.surface_commit_common(output.scale(), window_scales, surface);
+        self.surface_commit_common(output.scale(), window_rules, surface);
     }
 
     /// Recompute the window's size and location.